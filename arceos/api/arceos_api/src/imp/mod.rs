@@ -44,5 +44,6 @@ pub use self::stdio::*;
 pub use self::task::*;
 pub use self::time::*;
 
+pub use axhal::cpu::this_cpu_id as ax_this_cpu_id;
 pub use axhal::misc::terminate as ax_terminate;
 pub use axio::PollState as AxPollState;
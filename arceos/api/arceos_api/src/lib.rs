@@ -32,6 +32,8 @@ pub mod sys {
     define_api! {
         /// Shutdown the whole system and all CPUs.
         pub fn ax_terminate() -> !;
+        /// Returns the ID of the CPU the caller is running on.
+        pub fn ax_this_cpu_id() -> usize;
     }
 }
 
@@ -0,0 +1,47 @@
+//! 测试用的同步点：在某次原子读取之后、对应的写入或 CAS 之前暂停，
+//! 让测试在此处注入其它操作，确定性地复现并发交错。
+//!
+//! 只有启用 `test-sync` 特性时才生效，否则同步点是空函数，完全被编译掉。
+
+/// 同步点的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPoint {
+    /// 分配时读取 `byte_pos` 之后、推进它的 CAS 之前
+    BeforeBump,
+    /// 释放导致整体重置时确认计数为 0 之后、回退 `byte_pos` 的 CAS 之前
+    BeforeReset,
+}
+
+#[cfg(feature = "test-sync")]
+mod imp {
+    use super::SyncPoint;
+    use core::sync::atomic::{AtomicPtr, Ordering};
+
+    static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+    /// 设置在每个同步点调用的钩子，`None` 表示取消。
+    ///
+    /// 钩子是全局的，会在所有线程、所有分配器上触发。
+    pub fn set_sync_hook(hook: Option<fn(SyncPoint)>) {
+        let ptr = hook.map_or(core::ptr::null_mut(), |f| f as *mut ());
+        HOOK.store(ptr, Ordering::SeqCst);
+    }
+
+    pub(crate) fn sync_point(point: SyncPoint) {
+        let ptr = HOOK.load(Ordering::SeqCst);
+        if !ptr.is_null() {
+            // SAFETY: 非空时只可能是 `set_sync_hook` 存入的 `fn(SyncPoint)`
+            let hook = unsafe { core::mem::transmute::<*mut (), fn(SyncPoint)>(ptr) };
+            hook(point);
+        }
+    }
+}
+
+#[cfg(feature = "test-sync")]
+pub use imp::set_sync_hook;
+#[cfg(feature = "test-sync")]
+pub(crate) use imp::sync_point;
+
+#[cfg(not(feature = "test-sync"))]
+#[inline(always)]
+pub(crate) fn sync_point(_point: SyncPoint) {}
@@ -1,9 +1,22 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 use allocator::{AllocError, AllocResult, BaseAllocator, ByteAllocator, PageAllocator};
 use core::alloc::Layout;
 use core::ptr::NonNull;
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+mod hook;
+#[cfg(debug_assertions)]
+mod outstanding;
+mod split;
+mod state;
+mod stats;
+
+#[cfg(feature = "test-sync")]
+pub use hook::{set_sync_hook, SyncPoint};
+pub use split::{ByteOnlyAllocator, PageOnlyAllocator};
+pub use state::EarlyAllocatorState;
+pub use stats::{assert_no_leak, combined_stats, AllocDelta, AllocStats, HistoryRecorder};
 
 /// Early memory allocator
 /// Use it before formal bytes-allocator and pages-allocator can work!
@@ -17,9 +30,21 @@ use core::sync::atomic::{AtomicUsize, Ordering};
 ///
 /// For bytes area, 'count' records number of allocations.
 /// When it goes down to ZERO, free bytes-used area.
-/// For pages area, it will never be freed!
+/// Freeing the top-most allocation pops 'b_pos' back (LIFO), and 'floor'
+/// remembers the frontier below which allocations are still live, so that
+/// 'b_pos' returns to it once everything above has been freed.
+/// Regions taken by `reserve_bytes` are permanent and never reset.
+/// `set_reclaim_policy` selects count-only reset, LIFO (default) or no reclaim.
+/// Byte requests of exactly one page in both size and alignment are served
+/// from the pages area instead.
+/// For pages area, only freeing the most recently allocated block moves
+/// 'p_pos' back (LIFO); other freed pages are never reused.
 ///
+/// Before `init`, the `ByteAllocator`/`PageAllocator` accessors report
+/// meaningless zeros; use the `try_*` variants to tell them apart.
 pub struct EarlyAllocator<const PAGE_SIZE: usize> {
+    // 是否已调用过 init
+    initialized: bool,
     // 内存区域起始地址
     start: usize,
     // 内存区域结束地址
@@ -30,32 +55,1124 @@ pub struct EarlyAllocator<const PAGE_SIZE: usize> {
     page_pos: AtomicUsize,
     // 字节分配计数
     byte_count: AtomicUsize,
+    // 字节分配的最小对齐
+    min_align: usize,
+    // 回退下限：其下方的分配仍然存活
+    reset_floor: AtomicUsize,
+    // 位于回退下限下方的存活分配数
+    floor_count: AtomicUsize,
+    // 永久保留区域的末尾，重置不会越过这里
+    reserved_floor: AtomicUsize,
+    // 字节区使用率告警阈值（千分比）及回调
+    pressure_permille: u32,
+    pressure_cb: Option<fn()>,
+    // 告警回调是否可触发，降回阈值以下后重新装填
+    pressure_armed: AtomicBool,
+    // 下一次成功分配将获得的 ID，只增不减，重新 init 也不清零
+    next_alloc_id: AtomicU64,
+    // 冻结后拒绝一切新分配，释放不受影响
+    frozen: AtomicBool,
+    // 字节区的回收策略
+    reclaim_policy: ReclaimPolicy,
+    // 最近一次字节分配前插入的对齐填充，重置时清零
+    last_padding: AtomicUsize,
+    // 页分配为满足对齐累计跳过的字节数
+    page_waste: AtomicUsize,
+    // 计数归零后，至少要有这么多次字节分配才整体重置
+    reset_hysteresis: usize,
+    // 上次整体重置以来的字节分配次数
+    allocs_since_reset: AtomicUsize,
+    // alloc_stack 分配出的保护页数
+    guard_pages: AtomicUsize,
+    // 最近一次 carve_child 切出的区域 `(start, end)` 及切出前的 `page_pos`，归还后清除
+    last_carve: Option<(usize, usize, usize)>,
+    // 只做页分配（`split` 得到的页区一半），页可以一直分配到 `byte_pos`
+    page_only: bool,
+    // 按 2 的幂分桶统计的字节分配对齐填充，重置时清零
+    #[cfg(feature = "track")]
+    padding_hist: [AtomicUsize; PADDING_BUCKETS],
+    // 是否在每个字节分配末尾写入越界哨兵，仅调试构建
+    #[cfg(debug_assertions)]
+    canaries: bool,
+    // 最近一次写入的哨兵地址，0 表示没有
+    #[cfg(debug_assertions)]
+    last_canary: AtomicUsize,
+    // 存活的字节分配，仅调试构建，用于检测重复释放
+    #[cfg(debug_assertions)]
+    outstanding: outstanding::OutstandingTable,
+}
+
+/// 对齐填充直方图的桶数，第 i 个桶统计填充在 `[2^i, 2^(i+1))` 内的分配
+#[cfg(feature = "track")]
+pub const PADDING_BUCKETS: usize = 16;
+
+/// 调试构建下每个字节分配末尾的哨兵字节数及其取值
+#[cfg(debug_assertions)]
+const CANARY_LEN: usize = 8;
+#[cfg(debug_assertions)]
+const CANARY_BYTE: u8 = 0xa5;
+
+/// 分配时选择从内存区域的哪一端取
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    /// 低端：从字节区向前推进 `byte_pos`
+    Low,
+    /// 高端：从页区向后推进 `page_pos`，按整页计
+    High,
+}
+
+/// `alloc_pages_handle` 得到的页块，记录其起始地址和页数。
+///
+/// 不可复制，交给 `free_handle` 后即被消耗，避免同一页块被释放两次或以错误的页数释放。
+#[must_use]
+#[derive(Debug, PartialEq, Eq)]
+pub struct PageHandle {
+    base: usize,
+    num_pages: usize,
+}
+
+impl PageHandle {
+    /// 页块的起始地址
+    pub fn base(&self) -> usize {
+        self.base
+    }
+
+    /// 页块的页数
+    pub fn num_pages(&self) -> usize {
+        self.num_pages
+    }
+}
+
+/// 释放字节分配时如何回收空间
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReclaimPolicy {
+    /// 只在分配计数降为 0 时整体重置
+    CountReset,
+    /// 在整体重置之外，释放最顶端的分配时逐步回退 `byte_pos`（默认）
+    Lifo,
+    /// 从不回收，只向前推进，最快
+    Never,
+}
+
+/// 最常用的 4K 页大小的早期分配器
+pub type DefaultEarlyAllocator = EarlyAllocator<4096>;
+
+/// 以指定页大小构造早期分配器，在编译期检查 `P` 是否为 2 的幂。
+///
+/// 非法的页大小会导致编译失败：
+///
+/// ```compile_fail
+/// use bump_allocator::{with_page_size, EarlyAllocator};
+///
+/// static EARLY: EarlyAllocator<3000> = with_page_size::<3000>();
+/// ```
+pub const fn with_page_size<const P: usize>() -> EarlyAllocator<P> {
+    let () = EarlyAllocator::<P>::PAGE_SIZE_CHECK;
+    EarlyAllocator::new()
+}
+
+/// 两个分配器管理的 `[start, end)` 区间是否重叠，相邻不算重叠。
+///
+/// 用于在启动时断言各内存区域互不相交，避免错误解析的内存映射重复计入同一段内存。
+pub fn arenas_overlap<const P: usize>(a: &EarlyAllocator<P>, b: &EarlyAllocator<P>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+impl<const PAGE_SIZE: usize> Default for EarlyAllocator<PAGE_SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
+    /// 页大小必须是 2 的幂
+    const PAGE_SIZE_CHECK: () = assert!(
+        PAGE_SIZE.is_power_of_two(),
+        "PAGE_SIZE must be a power of two"
+    );
+
     pub const fn new() -> Self {
         Self {
+            initialized: false,
             start: 0,
             end: 0,
             byte_pos: AtomicUsize::new(0),
             page_pos: AtomicUsize::new(0),
             byte_count: AtomicUsize::new(0),
+            min_align: 1,
+            reset_floor: AtomicUsize::new(0),
+            floor_count: AtomicUsize::new(0),
+            reserved_floor: AtomicUsize::new(0),
+            pressure_permille: 0,
+            pressure_cb: None,
+            pressure_armed: AtomicBool::new(true),
+            next_alloc_id: AtomicU64::new(0),
+            frozen: AtomicBool::new(false),
+            reclaim_policy: ReclaimPolicy::Lifo,
+            last_padding: AtomicUsize::new(0),
+            page_waste: AtomicUsize::new(0),
+            reset_hysteresis: 0,
+            allocs_since_reset: AtomicUsize::new(0),
+            guard_pages: AtomicUsize::new(0),
+            last_carve: None,
+            page_only: false,
+            #[cfg(feature = "track")]
+            padding_hist: [const { AtomicUsize::new(0) }; PADDING_BUCKETS],
+            #[cfg(debug_assertions)]
+            canaries: false,
+            #[cfg(debug_assertions)]
+            last_canary: AtomicUsize::new(0),
+            #[cfg(debug_assertions)]
+            outstanding: outstanding::OutstandingTable::new(),
+        }
+    }
+
+    /// 是否已调用过 `init`
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// 初始化，并让每个字节分配都至少按 `min_align` 对齐（与请求的对齐取较大者）。
+    ///
+    /// `min_align` 必须是 2 的幂，否则返回 `InvalidParam` 且不做初始化。
+    pub fn init_with_min_align(
+        &mut self,
+        start: usize,
+        size: usize,
+        min_align: usize,
+    ) -> AllocResult {
+        if !min_align.is_power_of_two() {
+            return Err(AllocError::InvalidParam);
+        }
+        self.init(start, size);
+        self.min_align = min_align;
+        Ok(())
+    }
+
+    /// 字节分配的最小对齐
+    pub fn min_align(&self) -> usize {
+        self.min_align
+    }
+
+    /// 设置字节区的回收策略，见 [`ReclaimPolicy`]
+    pub fn set_reclaim_policy(&mut self, policy: ReclaimPolicy) {
+        self.reclaim_policy = policy;
+    }
+
+    /// 设置整体重置的滞后：分配计数归零时，只有自上次重置以来至少发生过 `n` 次
+    /// 字节分配才回退 `byte_pos`，避免存活数在 0 附近振荡时反复重置。
+    ///
+    /// 默认为 0，即每次归零都重置。只对 `CountReset` 的整体重置生效，
+    /// `Lifo` 释放最顶端的分配时总是弹回。
+    pub fn set_reset_hysteresis(&mut self, n: usize) {
+        self.reset_hysteresis = n;
+    }
+
+    /// 最近一次字节分配为满足对齐在其前面插入的填充字节数。
+    ///
+    /// 字节区整体重置后归零，直到下一次字节分配。
+    pub fn last_allocation_padding(&self) -> usize {
+        self.last_padding.load(Ordering::SeqCst)
+    }
+
+    /// 字节分配对齐填充的直方图：第 i 项是填充在 `[2^i, 2^(i+1))` 字节内的
+    /// 分配次数，最后一项也包含更大的填充；没有填充的分配不计入。
+    ///
+    /// 用于观察过度对齐浪费了多少内存。字节区整体重置或重新 `init` 时清零。
+    #[cfg(feature = "track")]
+    pub fn alignment_histogram(&self) -> [usize; PADDING_BUCKETS] {
+        core::array::from_fn(|i| self.padding_hist[i].load(Ordering::SeqCst))
+    }
+
+    /// 把一次字节分配插入的对齐填充计入直方图
+    fn record_padding(&self, padding: usize) {
+        #[cfg(feature = "track")]
+        if padding > 0 {
+            let bucket = (padding.ilog2() as usize).min(PADDING_BUCKETS - 1);
+            self.padding_hist[bucket].fetch_add(1, Ordering::SeqCst);
+        }
+        #[cfg(not(feature = "track"))]
+        let _ = padding;
+    }
+
+    /// 清空对齐填充直方图
+    fn clear_padding_histogram(&self) {
+        #[cfg(feature = "track")]
+        for bucket in &self.padding_hist {
+            bucket.store(0, Ordering::SeqCst);
+        }
+    }
+
+    /// 页分配为满足对齐累计跳过（且不再使用）的字节数，
+    /// 可用来解释 `available_pages` 为何比预期下降得快。
+    pub fn page_alignment_waste(&self) -> usize {
+        self.page_waste.load(Ordering::SeqCst)
+    }
+
+    /// 区域总字节数，`end < start`（状态已损坏）时返回 `None` 而不是回绕
+    pub fn total_bytes_checked(&self) -> Option<usize> {
+        self.end.checked_sub(self.start)
+    }
+
+    /// 在调试构建中为之后的 `alloc` 启用越界哨兵，用于捕获启动代码的差一错误。
+    ///
+    /// 每个字节分配末尾额外占用 8 字节并写入固定值，在下一次字节分配以及
+    /// 释放该分配时检查，被改写则 panic。应在有任何字节分配之前启用，且内存
+    /// 区域必须真实可写；`bump_bytes`/`reserve_bytes` 得到的区域不带哨兵。
+    ///
+    /// 发布构建中这是空操作，分配与未启用时完全一样。
+    pub fn enable_canaries(&mut self) {
+        #[cfg(debug_assertions)]
+        {
+            self.canaries = true;
+        }
+    }
+
+    /// 每个字节分配末尾的哨兵字节数，未启用或发布构建时为 0
+    fn canary_len(&self) -> usize {
+        #[cfg(debug_assertions)]
+        if self.canaries {
+            return CANARY_LEN;
+        }
+        0
+    }
+
+    /// 在 `addr` 处写入哨兵，并记为最近一次的哨兵
+    #[cfg(debug_assertions)]
+    fn write_canary(&self, addr: usize) {
+        // SAFETY: 哨兵位于刚推进过的字节区内，调用者保证内存区域可写
+        unsafe { core::ptr::write_bytes(addr as *mut u8, CANARY_BYTE, CANARY_LEN) };
+        self.last_canary.store(addr, Ordering::SeqCst);
+    }
+
+    /// 检查 `addr` 处的哨兵是否完好
+    #[cfg(debug_assertions)]
+    fn check_canary(&self, addr: usize) {
+        // SAFETY: 哨兵由 `write_canary` 写入，其所在空间尚未被回收
+        let bytes = unsafe { core::slice::from_raw_parts(addr as *const u8, CANARY_LEN) };
+        assert!(
+            bytes.iter().all(|&b| b == CANARY_BYTE),
+            "byte allocation overrun: canary at {:#x} was overwritten",
+            addr
+        );
+    }
+
+    /// 当前的字节区回收策略
+    pub fn reclaim_policy(&self) -> ReclaimPolicy {
+        self.reclaim_policy
+    }
+
+    /// 当前的回退下限：释放完其上方的所有分配后，`byte_pos` 会回到这里
+    pub fn reset_floor(&self) -> usize {
+        self.reset_floor.load(Ordering::SeqCst)
+    }
+
+    /// 当前的永久保留下限：任何重置都不会越过这里
+    pub fn reserved_floor(&self) -> usize {
+        self.reserved_floor.load(Ordering::SeqCst)
+    }
+
+    /// 保留一块永久的字节区域（例如启动阶段的页表）。
+    ///
+    /// 与 `alloc` 一样从字节区分配，但不计入分配计数，也不能被释放：
+    /// 之后释放其它字节分配引起的重置都只会回退到该区域的末尾。
+    pub fn reserve_bytes(&mut self, size: usize, align: usize) -> AllocResult<NonNull<u8>> {
+        let layout = Layout::from_size_align(size, align).map_err(|_| AllocError::InvalidParam)?;
+        let aligned_pos = self.bump_byte_pos(layout)?;
+        let new_pos = aligned_pos + size;
+
+        // 当前所有存活的分配都在保留区域下方
+        self.reserved_floor.store(new_pos, Ordering::SeqCst);
+        self.reset_floor.store(new_pos, Ordering::SeqCst);
+        self.floor_count
+            .store(self.byte_count.load(Ordering::SeqCst), Ordering::SeqCst);
+
+        Ok(NonNull::new(aligned_pos as *mut u8).unwrap())
+    }
+
+    /// 一次性释放所有临时的字节分配，但保留 `reserve_bytes` 得到的永久区域。
+    ///
+    /// `byte_pos` 只回退到保留下限而不是 `start`，分配计数清零；
+    /// 调用者须保证此前的临时分配都已不再使用。页区不受影响。
+    pub fn reset_transient(&mut self) {
+        let reserved = self.reserved_floor.load(Ordering::SeqCst);
+        self.byte_pos.store(reserved, Ordering::SeqCst);
+        self.reset_floor.store(reserved, Ordering::SeqCst);
+        self.floor_count.store(0, Ordering::SeqCst);
+        self.byte_count.store(0, Ordering::SeqCst);
+        self.last_padding.store(0, Ordering::SeqCst);
+        self.clear_padding_histogram();
+        self.allocs_since_reset.store(0, Ordering::SeqCst);
+        #[cfg(debug_assertions)]
+        {
+            self.last_canary.store(0, Ordering::SeqCst);
+            self.outstanding.clear();
+        }
+        self.check_pressure();
+    }
+
+    /// 字节区使用量占整个内存区域的千分比
+    pub fn byte_usage_permille(&self) -> u32 {
+        let total = self.total_bytes();
+        if total == 0 {
+            return 0;
+        }
+        (self.used_bytes() as u64 * 1000 / total as u64) as u32
+    }
+
+    /// 设置字节区使用率告警：`alloc` 使 `byte_usage_permille()` 首次达到
+    /// `permille` 时调用一次 `cb`，使用率降回阈值以下后可再次触发。
+    ///
+    /// `cb` 在分配路径上被调用，不能再分配内存或回调分配器。
+    pub fn set_pressure_callback(&mut self, permille: u32, cb: fn()) {
+        self.pressure_permille = permille;
+        self.pressure_cb = Some(cb);
+        self.pressure_armed.store(true, Ordering::SeqCst);
+    }
+
+    /// 检查使用率是否越过告警阈值
+    fn check_pressure(&self) {
+        let Some(cb) = self.pressure_cb else {
+            return;
+        };
+        if self.byte_usage_permille() < self.pressure_permille {
+            self.pressure_armed.store(true, Ordering::SeqCst);
+        } else if self.pressure_armed.swap(false, Ordering::SeqCst) {
+            cb();
+        }
+    }
+
+    /// 试算从当前字节位置起依次分配 `reqs` 的布局，不改变分配器状态。
+    ///
+    /// 返回 `(总占用字节数, 各请求相对当前位置的偏移)`，包含对齐填充；
+    /// 空间不足时返回 `NoMemory`。试算按字节区的规则进行，与
+    /// `alloc_from(.., End::Low)` 的结果一致。
+    pub fn plan_layout<const N: usize>(
+        &self,
+        reqs: &[Layout; N],
+    ) -> AllocResult<(usize, [usize; N])> {
+        let base = self.byte_pos.load(Ordering::SeqCst);
+        let mut pos = base;
+        let mut offsets = [0; N];
+        for (offset, layout) in offsets.iter_mut().zip(reqs) {
+            let aligned_pos = Self::align_up(pos, layout.align().max(self.min_align));
+            *offset = aligned_pos - base;
+            pos = aligned_pos + layout.size();
+        }
+        if pos > self.page_pos.load(Ordering::SeqCst) {
+            return Err(AllocError::NoMemory);
+        }
+        Ok((pos - base, offsets))
+    }
+
+    /// 将 `byte_pos` 恰好推进 `size` 字节（不做任何对齐，也不考虑 `min_align`），
+    /// 返回推进前的位置，便于调用者在其上自行实现对齐逻辑。
+    ///
+    /// 计为一次字节分配，可用 `Layout::from_size_align(size, 1)` 释放。
+    pub fn bump_bytes(&mut self, size: usize) -> AllocResult<usize> {
+        self.check_frozen()?;
+        let prev_pos = self.byte_pos.load(Ordering::SeqCst);
+        let new_pos = prev_pos.checked_add(size).ok_or(AllocError::NoMemory)?;
+        if new_pos > self.page_pos.load(Ordering::SeqCst) {
+            return Err(AllocError::NoMemory);
+        }
+        self.byte_pos.store(new_pos, Ordering::SeqCst);
+        self.byte_count.fetch_add(1, Ordering::SeqCst);
+        #[cfg(debug_assertions)]
+        self.outstanding.insert(prev_pos, false);
+        self.last_padding.store(0, Ordering::SeqCst);
+        self.stamp_alloc_id();
+        self.check_pressure();
+        Ok(prev_pos)
+    }
+
+    /// 在没有任何存活分配时，用整个 `[start, end)` 区间分配一块大缓冲区。
+    ///
+    /// 只要还有字节分配、页分配或保留区域存在就返回 `NoMemory`，以免重叠；
+    /// 释放时用 `Layout::from_size_align(size, align)` 调用 `dealloc`。
+    /// 整页大小、按页对齐的请求同样从 `start` 开始，不会转到页区。
+    pub fn alloc_contiguous(&mut self, size: usize, align: usize) -> AllocResult<NonNull<u8>> {
+        let layout = Layout::from_size_align(size, align).map_err(|_| AllocError::InvalidParam)?;
+        if self.byte_count.load(Ordering::SeqCst) != 0
+            || self.page_pos.load(Ordering::SeqCst) != self.end
+            || self.reserved_floor.load(Ordering::SeqCst) != self.start
+        {
+            return Err(AllocError::NoMemory);
+        }
+
+        // 没有存活分配，字节区可以从头开始
+        self.byte_pos.store(self.start, Ordering::SeqCst);
+        self.reset_floor.store(self.start, Ordering::SeqCst);
+        self.floor_count.store(0, Ordering::SeqCst);
+        self.alloc_bytes(layout)
+    }
+
+    /// 当前最大的连续空闲块大小，即 `page_pos - byte_pos`。
+    ///
+    /// 字节区和页区共用这一段空隙，`available_bytes`/`available_pages`
+    /// 只是从两端分别度量它，任何一侧的分配都会让它缩小。
+    pub fn largest_free_block(&self) -> usize {
+        self.page_pos.load(Ordering::SeqCst) - self.byte_pos.load(Ordering::SeqCst)
+    }
+
+    /// 从当前字节位置起，还能连续成功分配多少个 `layout`（计入每次的对齐填充）。
+    ///
+    /// 只按字节区的规则计算：不计调试构建中的越界哨兵，也不考虑整页请求会被
+    /// `alloc` 转到页区，因此可能多于实际能成功的 `alloc` 次数，需要精确值时用
+    /// [`packable_count`](Self::packable_count)。大小为 0 的布局返回 `usize::MAX`。
+    pub fn capacity_for(&self, layout: Layout) -> usize {
+        self.byte_fit_count(layout.size(), layout.align().max(self.min_align))
+    }
+
+    /// 从当前位置起，连续调用 `alloc(layout)` 能成功多少次，便于不经试分配就确定数组长度。
+    ///
+    /// 与 [`capacity_for`](Self::capacity_for) 不同，这里按 `alloc` 的实际行为计算：
+    /// 整页请求按页区剩余的页数计，字节请求计入每块后的越界哨兵（若已启用），
+    /// 冻结时为 0。
+    pub fn packable_count(&self, layout: Layout) -> usize {
+        if self.is_frozen() {
+            return 0;
+        }
+        if Self::is_page_layout(layout) {
+            // 与 alloc_pages 一致：页起始向下对齐，且不能低于页区下限
+            let floor = self.page_floor(self.byte_pos.load(Ordering::SeqCst));
+            return match self.page_pos.load(Ordering::SeqCst).checked_sub(PAGE_SIZE) {
+                Some(pos) if pos & !(PAGE_SIZE - 1) >= floor => {
+                    ((pos & !(PAGE_SIZE - 1)) - floor) / PAGE_SIZE + 1
+                }
+                _ => 0,
+            };
+        }
+        self.byte_fit_count(
+            layout.size() + self.canary_len(),
+            layout.align().max(self.min_align),
+        )
+    }
+
+    /// 从当前字节位置起能连续排下多少个 `size` 字节、按 `align` 对齐的块
+    fn byte_fit_count(&self, size: usize, align: usize) -> usize {
+        let page_pos = self.page_pos.load(Ordering::SeqCst);
+        let first = Self::align_up(self.byte_pos.load(Ordering::SeqCst), align);
+        if first + size > page_pos {
+            return 0;
+        }
+        // 第一个之后，每个分配都从对齐位置开始，步长固定
+        let stride = Self::align_up(size, align);
+        if stride == 0 {
+            return usize::MAX;
+        }
+        (page_pos - first - size) / stride + 1
+    }
+
+    /// 从页区切出 `num_pages` 页，作为一个独立的子分配器返回
+    pub fn carve_child(&mut self, num_pages: usize) -> AllocResult<Self> {
+        let prev_pos = self.page_pos.load(Ordering::SeqCst);
+        let start = self.alloc_pages(num_pages, PAGE_SIZE)?;
+        let end = start + num_pages * PAGE_SIZE;
+        let mut child = Self::new();
+        child.init(start, end - start);
+        self.last_carve = Some((start, end, prev_pos));
+        Ok(child)
+    }
+
+    /// 归还由 `carve_child` 切出的子分配器，回收其页区域。
+    ///
+    /// 子分配器的 `[start, end)` 必须恰好是最近一次切出的区域，且之后没有再
+    /// 分配过页（其起始地址恰为当前 `page_pos`），否则返回 `InvalidParam`；
+    /// 手工构造或范围不符的分配器都会被拒绝。
+    ///
+    /// 归还后 `page_pos` 恢复到切出之前的位置，连同为页对齐跳过的字节一并收回。
+    pub fn reclaim_child(&mut self, child: Self) -> AllocResult<()> {
+        let page_pos = self.page_pos.load(Ordering::SeqCst);
+        let Some((start, end, prev_pos)) = self.last_carve else {
+            return Err(AllocError::InvalidParam);
+        };
+        if !child.is_initialized() || (child.start, child.end) != (start, end) || start != page_pos
+        {
+            return Err(AllocError::InvalidParam);
+        }
+        self.page_pos.store(prev_pos, Ordering::SeqCst);
+        self.page_waste.fetch_sub(prev_pos - end, Ordering::SeqCst);
+        self.last_carve = None;
+        Ok(())
+    }
+
+    /// 将字节区与页区之间的空闲区域 `[byte_pos, page_pos)` 全部清零，
+    /// 不会触及两侧已分配的内存。耗时与空闲区域大小成正比，可重复调用。
+    ///
+    /// # Safety
+    ///
+    /// 调用者须保证 `init` 时传入的内存区域真实可写，且没有其它代码并发访问空闲区域。
+    pub unsafe fn zero_free_region(&mut self) {
+        let byte_pos = self.byte_pos.load(Ordering::SeqCst);
+        let page_pos = self.page_pos.load(Ordering::SeqCst);
+        if page_pos > byte_pos {
+            core::ptr::write_bytes(byte_pos as *mut u8, 0, page_pos - byte_pos);
+        }
+    }
+
+    /// 以切片形式查看字节区已使用的部分 `[start, byte_pos)`，
+    /// 便于计算校验和或在崩溃转储中保存，包含对齐填充和保留区域。
+    ///
+    /// # Safety
+    ///
+    /// 调用者须保证 `init` 时传入的内存区域真实可读，且在返回的切片存活期间
+    /// 没有其它代码修改这段内存（包括通过此前分配得到的指针写入）。
+    pub unsafe fn used_byte_region(&self) -> &[u8] {
+        let byte_pos = self.byte_pos.load(Ordering::SeqCst);
+        core::slice::from_raw_parts(self.start as *const u8, byte_pos - self.start)
+    }
+
+    /// 早期分配结束、交接给正式分配器时，已经分配出去的两段区域：
+    /// 字节区 `[start, byte_pos)` 与页区 `[page_pos, end)`。
+    ///
+    /// 后继分配器应把它们标记为保留，不再分配。两段区域互不重叠，
+    /// 其间的 `[byte_pos, page_pos)` 可以交给后继分配器使用。
+    pub fn handoff_regions(&self) -> (core::ops::Range<usize>, core::ops::Range<usize>) {
+        let byte_pos = self.byte_pos.load(Ordering::SeqCst);
+        let page_pos = self.page_pos.load(Ordering::SeqCst);
+        (self.start..byte_pos, page_pos..self.end)
+    }
+
+    /// 在 `range` 内每页读取一个字节，让按需调页的物理区域提前驻留，
+    /// 避免关键路径上首次访问的延迟。返回触及的页数。
+    ///
+    /// `range` 会被截取到 `[start, end)` 之内，区域外的地址不会被访问；
+    /// 截取后为空时什么也不做。
+    ///
+    /// # Safety
+    ///
+    /// 调用者须保证 `init` 时传入的内存区域真实可读。
+    pub unsafe fn prefault(&mut self, range: core::ops::Range<usize>) -> usize {
+        let lo = range.start.max(self.start);
+        let hi = range.end.min(self.end);
+        let mut touched = 0;
+        let mut addr = lo;
+        while addr < hi {
+            core::ptr::read_volatile(addr as *const u8);
+            touched += 1;
+            addr = (addr & !(PAGE_SIZE - 1)) + PAGE_SIZE;
+        }
+        touched
+    }
+
+    /// 分配按页对齐的缓冲区（例如 DMA 缓冲区）：`size` 向上取整到整页后从页区分配，
+    /// 不会像经由 `alloc` 按页对齐那样在字节区留下最多一页的填充。
+    ///
+    /// `size` 为 0 时返回 `InvalidParam`；释放时用取整后的页数调用 `dealloc_pages`。
+    pub fn alloc_page_aligned(&mut self, size: usize) -> AllocResult<NonNull<u8>> {
+        if size == 0 {
+            return Err(AllocError::InvalidParam);
+        }
+        let pos = self.alloc_pages(size.div_ceil(PAGE_SIZE), PAGE_SIZE)?;
+        Ok(NonNull::new(pos as *mut u8).unwrap())
+    }
+
+    /// 把 `size` 向上取整到 2 的幂，并按同一值对齐分配，便于之后像伙伴系统那样合并。
+    ///
+    /// 经由 `alloc` 分配，释放时用 `Layout::from_size_align(n, n)`（`n` 为取整后的
+    /// 大小）调用 `dealloc`。`size` 为 0 时返回 `InvalidParam`，取整溢出返回 `NoMemory`。
+    pub fn alloc_pow2(&mut self, size: usize) -> AllocResult<NonNull<u8>> {
+        if size == 0 {
+            return Err(AllocError::InvalidParam);
+        }
+        let n = size
+            .checked_next_power_of_two()
+            .ok_or(AllocError::NoMemory)?;
+        let layout = Layout::from_size_align(n, n).map_err(|_| AllocError::NoMemory)?;
+        self.alloc(layout)
+    }
+
+    /// 为一个 `T` 分配未初始化的空间，返回带类型的指针。
+    ///
+    /// 按 `Layout::new::<T>()` 经 `alloc` 分配，释放时用同一布局调用 `dealloc`。
+    /// 零大小类型不占用空间，直接返回对齐的悬空指针，不应释放。
+    ///
+    /// # Safety
+    ///
+    /// 返回的内存未初始化，读取前必须先写入。
+    pub unsafe fn alloc_obj<T>(&mut self) -> AllocResult<NonNull<T>> {
+        let layout = Layout::new::<T>();
+        if layout.size() == 0 {
+            return Ok(NonNull::dangling());
+        }
+        self.alloc(layout).map(NonNull::cast)
+    }
+
+    /// 与 [`alloc_obj`](Self::alloc_obj) 相同，并把 `value` 写入分配到的空间。
+    ///
+    /// # Safety
+    ///
+    /// `init` 时传入的内存区域必须真实可写。
+    pub unsafe fn alloc_obj_init<T>(&mut self, value: T) -> AllocResult<NonNull<T>> {
+        let ptr = self.alloc_obj::<T>()?;
+        ptr.as_ptr().write(value);
+        Ok(ptr)
+    }
+
+    /// 原地把最近一次字节分配从 `old_size` 扩大到 `new_size` 字节，不移动数据。
+    ///
+    /// 只有 `ptr + old_size` 恰好等于当前的 `byte_pos`（即 `ptr` 确实是字节区最顶端的
+    /// 分配）时才会推进 `byte_pos`；过期或错误的指针返回 `InvalidParam` 且不改变状态，
+    /// 避免破坏字节区。`new_size < old_size` 同样返回 `InvalidParam`，空间不足返回
+    /// `NoMemory`。之后应以 `new_size` 释放这块分配。
+    pub fn grow_last(&mut self, ptr: NonNull<u8>, old_size: usize, new_size: usize) -> AllocResult {
+        self.check_frozen()?;
+        let pos = ptr.as_ptr() as usize;
+        let canary = self.canary_len();
+        let byte_pos = self.byte_pos.load(Ordering::SeqCst);
+        if new_size < old_size || pos.checked_add(old_size + canary) != Some(byte_pos) {
+            return Err(AllocError::InvalidParam);
+        }
+        let new_pos = pos
+            .checked_add(new_size + canary)
+            .ok_or(AllocError::NoMemory)?;
+        if new_pos > self.page_pos.load(Ordering::SeqCst) {
+            return Err(AllocError::NoMemory);
+        }
+        self.byte_pos.store(new_pos, Ordering::SeqCst);
+        #[cfg(debug_assertions)]
+        if self.canaries {
+            self.write_canary(pos + new_size);
+        }
+        self.check_pressure();
+        Ok(())
+    }
+
+    /// `layout` 在这个分配器上是否有可能被满足，即字节区在没有任何临时分配时
+    /// 能否放下它；整页请求则检查页区。
+    ///
+    /// 返回 `false` 表示无论释放多少都不可能成功：区域太小、区域基址无法提供
+    /// 所需的对齐，或空间已被 `reserve_bytes` 永久占去。返回 `true` 并不保证此刻
+    /// 的 `alloc` 会成功。未初始化时返回 `false`。
+    pub fn supports_layout(&self, layout: Layout) -> bool {
+        if !self.initialized {
+            return false;
+        }
+        if Self::is_page_layout(layout) {
+            // 页区从 end 向下分配，页起始必须严格高于字节区
+            let floor = self.reserved_floor.load(Ordering::SeqCst);
+            return self
+                .end
+                .checked_sub(PAGE_SIZE)
+                .is_some_and(|pos| pos & !(PAGE_SIZE - 1) > floor);
+        }
+        let align = layout.align().max(self.min_align);
+        let size = layout.size() + self.canary_len();
+        self.reserved_floor
+            .load(Ordering::SeqCst)
+            .checked_next_multiple_of(align)
+            .and_then(|pos| pos.checked_add(size))
+            .is_some_and(|end| end <= self.end)
+    }
+
+    /// 尽量按 `preferred_align` 对齐分配 `size` 字节：对齐填充导致空间不足时，
+    /// 将对齐逐次减半重试，直到 `min_align`。返回指针和实际达到的对齐，
+    /// 释放时以该对齐构造布局调用 `dealloc`。
+    ///
+    /// 两个对齐都必须是 2 的幂且 `min_align <= preferred_align`，否则返回
+    /// `InvalidParam`；按 `min_align` 仍放不下时返回 `NoMemory`。
+    pub fn alloc_best_effort(
+        &mut self,
+        size: usize,
+        preferred_align: usize,
+        min_align: usize,
+    ) -> AllocResult<(NonNull<u8>, usize)> {
+        if !preferred_align.is_power_of_two()
+            || !min_align.is_power_of_two()
+            || min_align > preferred_align
+        {
+            return Err(AllocError::InvalidParam);
+        }
+        let mut align = preferred_align;
+        loop {
+            let layout =
+                Layout::from_size_align(size, align).map_err(|_| AllocError::InvalidParam)?;
+            match self.alloc(layout) {
+                Err(AllocError::NoMemory) if align > min_align => align /= 2,
+                result => return result.map(|ptr| (ptr, align)),
+            }
+        }
+    }
+
+    /// 分配不跨越页边界的块，供不能跨页做 DMA 的设备使用。
+    ///
+    /// 若按 `align` 自然对齐的位置会跨越 `PAGE_SIZE` 边界，先将 `byte_pos`
+    /// 推进到下一页的起始处，使返回的块完整落在同一页内。`size` 超过一页或
+    /// 布局非法时返回 `InvalidParam`；释放时用 `Layout::from_size_align(size, align)`
+    /// 调用 `dealloc`。
+    pub fn alloc_no_page_cross(&mut self, size: usize, align: usize) -> AllocResult<NonNull<u8>> {
+        if size > PAGE_SIZE {
+            return Err(AllocError::InvalidParam);
+        }
+        let layout = Layout::from_size_align(size, align).map_err(|_| AllocError::InvalidParam)?;
+        let pos = Self::align_up(
+            self.byte_pos.load(Ordering::SeqCst),
+            align.max(self.min_align),
+        );
+        if size > 0 && pos / PAGE_SIZE != (pos + size - 1) / PAGE_SIZE {
+            // 按页对齐后必然落在同一页内
+            let bumped = Layout::from_size_align(size, align.max(PAGE_SIZE)).unwrap();
+            return self.alloc_bytes(bumped);
+        }
+        self.alloc(layout)
+    }
+
+    /// 为内核线程分配带保护页的栈：从页区取 `pages + 1` 页，最低的一页作为保护页，
+    /// 返回 `(stack_base, guard_base)`，可用的栈为 `[stack_base, stack_base + pages * PAGE_SIZE)`。
+    ///
+    /// 保护页计入 [`guard_pages`](Self::guard_pages)，调用者应将其映射为不可访问。
+    /// 它位于栈的下方，仅用 `dealloc_pages(stack_base, pages)` 归还栈时不会被重新分配。
+    /// `pages` 为 0 时返回 `InvalidParam`。
+    pub fn alloc_stack(&mut self, pages: usize) -> AllocResult<(usize, usize)> {
+        if pages == 0 {
+            return Err(AllocError::InvalidParam);
+        }
+        let total = pages.checked_add(1).ok_or(AllocError::NoMemory)?;
+        let guard_base = self.alloc_pages(total, PAGE_SIZE)?;
+        self.guard_pages.fetch_add(1, Ordering::SeqCst);
+        Ok((guard_base + PAGE_SIZE, guard_base))
+    }
+
+    /// 与 `alloc_pages` 相同，但返回记录了起始地址和页数的 [`PageHandle`]，
+    /// 之后交给 [`free_handle`](Self::free_handle) 释放，不必自行保存 `(pos, num_pages)`。
+    pub fn alloc_pages_handle(
+        &mut self,
+        num_pages: usize,
+        align_pow2: usize,
+    ) -> AllocResult<PageHandle> {
+        let base = self.alloc_pages(num_pages, align_pow2)?;
+        Ok(PageHandle { base, num_pages })
+    }
+
+    /// 按句柄记录的起始地址和页数释放页块，规则与 `dealloc_pages` 相同：
+    /// 只有最近一次分配的页块会被回收，其余的句柄被消耗但页不再复用。
+    pub fn free_handle(&mut self, h: PageHandle) {
+        self.dealloc_pages(h.base, h.num_pages);
+    }
+
+    /// `alloc_stack` 至今分配出的保护页数
+    pub fn guard_pages(&self) -> usize {
+        self.guard_pages.load(Ordering::SeqCst)
+    }
+
+    /// 显式指定从哪一端分配，而不论大小。
+    ///
+    /// `End::High` 的分配会向上取整到整页并至少按页对齐，计入页区的使用量；
+    /// 与页分配一样，它不应通过 `dealloc` 释放。
+    pub fn alloc_from(&mut self, layout: Layout, end: End) -> AllocResult<NonNull<u8>> {
+        match end {
+            End::Low => self.alloc_bytes(layout),
+            End::High => {
+                let num_pages = layout.size().div_ceil(PAGE_SIZE);
+                let pos = self.alloc_pages(num_pages, layout.align().max(PAGE_SIZE))?;
+                Ok(NonNull::new(pos as *mut u8).unwrap())
+            }
+        }
+    }
+
+    /// 页分配可以使用的最低地址：通常须严格高于 `byte_pos`，
+    /// 只做页分配时可以一直分配到 `byte_pos` 本身
+    fn page_floor(&self, byte_pos: usize) -> usize {
+        if self.page_only {
+            byte_pos
+        } else {
+            byte_pos + 1
+        }
+    }
+
+    /// 是否为走页区快速路径的整页请求
+    fn is_page_layout(layout: Layout) -> bool {
+        layout.size() == PAGE_SIZE && layout.align() == PAGE_SIZE
+    }
+
+    /// 从字节区分配
+    ///
+    /// 先增加计数再推进 `byte_pos`，这样并发的 `dealloc_bytes` 在重置前
+    /// 总能看到进行中的分配。
+    fn alloc_bytes(&self, layout: Layout) -> AllocResult<NonNull<u8>> {
+        #[cfg(debug_assertions)]
+        match self.last_canary.load(Ordering::SeqCst) {
+            0 => {}
+            addr => self.check_canary(addr),
+        }
+        // 为哨兵多占用的空间
+        let size = layout.size() + self.canary_len();
+        let padded =
+            Layout::from_size_align(size, layout.align()).map_err(|_| AllocError::NoMemory)?;
+
+        // 增加分配计数
+        self.byte_count.fetch_add(1, Ordering::SeqCst);
+
+        let aligned_pos = match self.bump_byte_pos(padded) {
+            Ok(pos) => pos,
+            Err(err) => {
+                self.byte_count.fetch_sub(1, Ordering::SeqCst);
+                return Err(err);
+            }
+        };
+        #[cfg(debug_assertions)]
+        {
+            if self.canaries {
+                self.write_canary(aligned_pos + layout.size());
+            }
+            self.outstanding.insert(aligned_pos, self.canaries);
+        }
+        self.allocs_since_reset.fetch_add(1, Ordering::SeqCst);
+        self.stamp_alloc_id();
+        self.check_pressure();
+
+        // 返回分配的内存指针
+        Ok(NonNull::new(aligned_pos as *mut u8).unwrap())
+    }
+
+    /// 释放字节区的分配
+    ///
+    /// 所有回退 `byte_pos` 的操作都用 `compare_exchange`，只在读取之后没有
+    /// 新的分配时才生效，否则放弃回退（只是暂时浪费空间）。
+    ///
+    /// 调试构建中释放一个不再存活的地址（重复释放）会 panic；发布构建不做检查。
+    fn dealloc_bytes(&self, pos: NonNull<u8>, layout: Layout) {
+        let pos = pos.as_ptr() as usize;
+        // 分配（连同哨兵）的末尾，无法确定时为 `None`，此时不做 LIFO 弹回
+        #[cfg(not(debug_assertions))]
+        let end = Some(pos + layout.size());
+        #[cfg(debug_assertions)]
+        let end = match self.outstanding.remove(pos) {
+            outstanding::Removed::Recorded { canary: true } => {
+                self.check_canary(pos + layout.size());
+                Some(pos + layout.size() + CANARY_LEN)
+            }
+            // `bump_bytes` 得到的区域不带哨兵
+            outstanding::Removed::Recorded { canary: false } => Some(pos + layout.size()),
+            // 未记录的分配不知道是否带哨兵，既不检查也不按末尾弹回
+            outstanding::Removed::Unrecorded => (!self.canaries).then_some(pos + layout.size()),
+            outstanding::Removed::Missing => {
+                panic!(
+                    "double free: byte allocation at {:#x} is not outstanding",
+                    pos
+                )
+            }
+        };
+
+        // 减少分配计数
+        let count = self.byte_count.fetch_sub(1, Ordering::SeqCst) - 1;
+        #[cfg(debug_assertions)]
+        if count == 0 {
+            self.outstanding.all_freed();
+        }
+
+        // 释放的是下限以下的分配，下限以下的存活数随之减少
+        if pos < self.reset_floor.load(Ordering::SeqCst) {
+            self.floor_count.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        let byte_pos = self.byte_pos.load(Ordering::SeqCst);
+        match self.reclaim_policy {
+            ReclaimPolicy::Never => {}
+            ReclaimPolicy::CountReset => {
+                if count == 0
+                    && self.allocs_since_reset.load(Ordering::SeqCst) >= self.reset_hysteresis
+                {
+                    self.reset_to_reserved(byte_pos);
+                }
+            }
+            ReclaimPolicy::Lifo => {
+                if count == 0 {
+                    self.reset_to_reserved(byte_pos);
+                } else if end == Some(byte_pos) {
+                    // 释放的是最顶端的分配：弹回其起始位置，剩余的存活分配都在其下方；
+                    // 若下限以上已全部释放，则直接回退到下限
+                    let floor = self.reset_floor.load(Ordering::SeqCst);
+                    let new_pos = if count == self.floor_count.load(Ordering::SeqCst) {
+                        pos.min(floor)
+                    } else {
+                        pos
+                    };
+                    if self.rewind_byte_pos(byte_pos, new_pos) {
+                        self.reset_floor.store(new_pos, Ordering::SeqCst);
+                        self.floor_count.store(count, Ordering::SeqCst);
+                    }
+                } else if count == self.floor_count.load(Ordering::SeqCst) {
+                    // 下限以上的分配已全部释放，回退到下限
+                    self.rewind_byte_pos(byte_pos, self.reset_floor.load(Ordering::SeqCst));
+                }
+            }
+        }
+        self.check_pressure();
+    }
+
+    /// 计数为 0 时重置字节分配器位置（保留区域除外）；
+    /// 先读 byte_pos 再确认计数，期间若有新分配则 CAS 失败
+    fn reset_to_reserved(&self, byte_pos: usize) {
+        let reserved = self.reserved_floor.load(Ordering::SeqCst);
+        if self.byte_count.load(Ordering::SeqCst) != 0 {
+            return;
+        }
+        hook::sync_point(hook::SyncPoint::BeforeReset);
+        if self.rewind_byte_pos(byte_pos, reserved) {
+            self.reset_floor.store(reserved, Ordering::SeqCst);
+            self.floor_count.store(0, Ordering::SeqCst);
+            self.last_padding.store(0, Ordering::SeqCst);
+            self.clear_padding_histogram();
+            self.allocs_since_reset.store(0, Ordering::SeqCst);
+        }
+    }
+
+    /// 若 `byte_pos` 仍为 `current`，将其回退到 `new_pos`，返回是否成功
+    fn rewind_byte_pos(&self, current: usize, new_pos: usize) -> bool {
+        let ok = self
+            .byte_pos
+            .compare_exchange(current, new_pos, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok();
+        // 被回收空间里的哨兵不再检查
+        #[cfg(debug_assertions)]
+        if ok && self.last_canary.load(Ordering::SeqCst) >= new_pos {
+            self.last_canary.store(0, Ordering::SeqCst);
+        }
+        ok
+    }
+
+    /// 地址 `addr` 所在页在内存区域内的序号（从 0 开始），不在 `[start, end)` 内时返回 `None`
+    pub fn page_index_of(&self, addr: usize) -> Option<usize> {
+        (self.start..self.end)
+            .contains(&addr)
+            .then(|| (addr - self.start) / PAGE_SIZE)
+    }
+
+    /// 第 `index` 页的起始地址，即 [`page_index_of`](Self::page_index_of) 的逆运算；
+    /// 序号超出区域的页数时返回 `None`
+    pub fn page_base(&self, index: usize) -> Option<usize> {
+        (index < self.total_pages()).then(|| self.start + index * PAGE_SIZE)
+    }
+
+    /// 第 `index` 页当前是否被页区占用，序号超出区域的页数时返回 `None`。
+    ///
+    /// 本分配器没有页的空闲链表或位图：页区从高端向下连续分配，
+    /// 因此 `p_pos` 以上的页都视为已分配；归还最近分配的页块后这些页重新变为未分配。
+    ///
+    /// 注意：`dealloc_pages` 只回收最顶端的页块，释放其它页块不会移动 `p_pos`，
+    /// 这些页之后也不会被回收，会一直报告为已分配。
+    pub fn is_page_allocated(&self, index: usize) -> Option<bool> {
+        self.page_base(index)
+            .map(|base| base >= self.page_pos.load(Ordering::SeqCst))
+    }
+
+    /// 下一次成功的 `alloc`/`alloc_pages` 将获得的分配 ID。
+    ///
+    /// ID 从 0 开始，每次成功分配加一，失败的分配不消耗 ID，且永不复用，
+    /// 可在日志中用来关联同一次分配。
+    pub fn next_alloc_id(&self) -> u64 {
+        self.next_alloc_id.load(Ordering::SeqCst)
+    }
+
+    /// 为一次成功的分配领取 ID
+    fn stamp_alloc_id(&self) -> u64 {
+        self.next_alloc_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// 冻结分配器：此后的 `alloc`/`alloc_pages` 等分配都返回 `NoMemory`，
+    /// 直到调用 [`unfreeze`](Self::unfreeze)；释放仍然正常进行。
+    ///
+    /// 用于在某个启动阶段之后捕获意外的早期分配。
+    pub fn freeze(&self) {
+        self.frozen.store(true, Ordering::SeqCst);
+    }
+
+    /// 解除 [`freeze`](Self::freeze)，恢复分配
+    pub fn unfreeze(&self) {
+        self.frozen.store(false, Ordering::SeqCst);
+    }
+
+    /// 是否处于冻结状态
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.load(Ordering::SeqCst)
+    }
+
+    /// 冻结时拒绝分配
+    fn check_frozen(&self) -> AllocResult {
+        if self.is_frozen() {
+            Err(AllocError::NoMemory)
+        } else {
+            Ok(())
         }
     }
 
+    /// 检查内部状态是否满足 `start <= byte_pos <= page_pos <= end`
+    pub fn check_invariants(&self) -> bool {
+        let byte_pos = self.byte_pos.load(Ordering::SeqCst);
+        let page_pos = self.page_pos.load(Ordering::SeqCst);
+        self.start <= byte_pos && byte_pos <= page_pos && page_pos <= self.end
+    }
+
     /// 对齐地址到指定的对齐要求
     fn align_up(addr: usize, align: usize) -> usize {
         (addr + align - 1) & !(align - 1)
     }
+
+    /// 在字节区按 `layout` 推进 `byte_pos`，返回分配的起始地址
+    fn bump_byte_pos(&self, layout: Layout) -> AllocResult<usize> {
+        self.check_frozen()?;
+        let align = layout.align().max(self.min_align);
+        let size = layout.size();
+        let page_pos = self.page_pos.load(Ordering::SeqCst);
+
+        let mut current_pos = self.byte_pos.load(Ordering::SeqCst);
+        loop {
+            // 计算对齐后的当前字节位置
+            let aligned_pos = Self::align_up(current_pos, align);
+
+            // 计算分配后的新位置
+            let new_pos = aligned_pos + size;
+
+            // 检查是否有足够的空间
+            if new_pos > page_pos {
+                return Err(AllocError::NoMemory);
+            }
+
+            // 更新字节位置，期间被其它分配抢先则重试
+            hook::sync_point(hook::SyncPoint::BeforeBump);
+            match self.byte_pos.compare_exchange(
+                current_pos,
+                new_pos,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => {
+                    self.last_padding
+                        .store(aligned_pos - current_pos, Ordering::SeqCst);
+                    self.record_padding(aligned_pos - current_pos);
+                    return Ok(aligned_pos);
+                }
+                Err(actual) => current_pos = actual,
+            }
+        }
+    }
 }
 
 impl<const PAGE_SIZE: usize> BaseAllocator for EarlyAllocator<PAGE_SIZE> {
     fn init(&mut self, start: usize, size: usize) {
+        self.initialized = true;
         self.start = start;
         self.end = start + size;
         self.byte_pos.store(start, Ordering::SeqCst);
         self.page_pos.store(self.end, Ordering::SeqCst);
         self.byte_count.store(0, Ordering::SeqCst);
+        self.min_align = 1;
+        self.reset_floor.store(start, Ordering::SeqCst);
+        self.floor_count.store(0, Ordering::SeqCst);
+        self.reserved_floor.store(start, Ordering::SeqCst);
+        self.last_padding.store(0, Ordering::SeqCst);
+        self.clear_padding_histogram();
+        self.page_waste.store(0, Ordering::SeqCst);
+        self.guard_pages.store(0, Ordering::SeqCst);
+        self.last_carve = None;
+        self.allocs_since_reset.store(0, Ordering::SeqCst);
+        #[cfg(debug_assertions)]
+        {
+            self.last_canary.store(0, Ordering::SeqCst);
+            self.outstanding.clear();
+        }
     }
 
     fn add_memory(&mut self, _start: usize, _size: usize) -> AllocResult {
@@ -65,41 +1182,25 @@ impl<const PAGE_SIZE: usize> BaseAllocator for EarlyAllocator<PAGE_SIZE> {
 }
 
 impl<const PAGE_SIZE: usize> ByteAllocator for EarlyAllocator<PAGE_SIZE> {
+    /// 大小和对齐都恰好为一页的请求直接从页区分配，避免在字节区产生碎片；
+    /// 其余请求从字节区分配。
     fn alloc(&mut self, layout: Layout) -> AllocResult<NonNull<u8>> {
-        let align = layout.align();
-        let size = layout.size();
-
-        // 计算对齐后的当前字节位置
-        let current_pos = self.byte_pos.load(Ordering::SeqCst);
-        let aligned_pos = Self::align_up(current_pos, align);
-
-        // 计算分配后的新位置
-        let new_pos = aligned_pos + size;
-
-        // 检查是否有足够的空间
-        let page_pos = self.page_pos.load(Ordering::SeqCst);
-        if new_pos > page_pos {
-            return Err(AllocError::NoMemory);
+        if Self::is_page_layout(layout) {
+            let pos = self.alloc_pages(1, PAGE_SIZE)?;
+            return Ok(NonNull::new(pos as *mut u8).unwrap());
         }
-
-        // 更新字节位置
-        self.byte_pos.store(new_pos, Ordering::SeqCst);
-
-        // 增加分配计数
-        self.byte_count.fetch_add(1, Ordering::SeqCst);
-
-        // 返回分配的内存指针
-        Ok(NonNull::new(aligned_pos as *mut u8).unwrap())
+        self.alloc_bytes(layout)
     }
 
-    fn dealloc(&mut self, _pos: NonNull<u8>, _layout: Layout) {
-        // 减少分配计数
-        let count = self.byte_count.fetch_sub(1, Ordering::SeqCst);
-
-        // 如果计数为0，重置字节分配器位置
-        if count == 1 {
-            self.byte_pos.store(self.start, Ordering::SeqCst);
+    /// 整页请求按地址判断来自哪一侧：`alloc_contiguous` 得到的整页分配在字节区。
+    fn dealloc(&mut self, pos: NonNull<u8>, layout: Layout) {
+        if Self::is_page_layout(layout)
+            && pos.as_ptr() as usize >= self.page_pos.load(Ordering::SeqCst)
+        {
+            self.dealloc_pages(pos.as_ptr() as usize, 1);
+            return;
         }
+        self.dealloc_bytes(pos, layout)
     }
 
     fn total_bytes(&self) -> usize {
@@ -124,8 +1225,10 @@ impl<const PAGE_SIZE: usize> ByteAllocator for EarlyAllocator<PAGE_SIZE> {
 
 impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
     const PAGE_SIZE: usize = PAGE_SIZE;
-    ///
+
     fn alloc_pages(&mut self, num_pages: usize, align_pow2: usize) -> AllocResult<usize> {
+        self.check_frozen()?;
+
         // 计算需要的总字节数
         let size = num_pages * PAGE_SIZE;
 
@@ -141,35 +1244,55 @@ impl<const PAGE_SIZE: usize> PageAllocator for EarlyAllocator<PAGE_SIZE> {
 
         // 检查是否有足够的空间
         let byte_pos = self.byte_pos.load(Ordering::SeqCst);
-        if aligned_pos <= byte_pos {
+        if aligned_pos < self.page_floor(byte_pos) {
             return Err(AllocError::NoMemory);
         }
 
-        // 更新页分配器位置
+        // 更新页分配器位置，记录为对齐跳过的字节
         self.page_pos.store(aligned_pos, Ordering::SeqCst);
+        self.page_waste
+            .fetch_add(new_pos - aligned_pos, Ordering::SeqCst);
+        self.stamp_alloc_id();
 
         // 返回分配的页起始地址
         Ok(aligned_pos)
     }
 
-    fn dealloc_pages(&mut self, _pos: usize, _num_pages: usize) {}
+    fn dealloc_pages(&mut self, pos: usize, num_pages: usize) {
+        // 只有最近一次分配的页块可以归还，其余的页不再复用
+        let _ = self.page_pos.compare_exchange(
+            pos,
+            pos + num_pages * PAGE_SIZE,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+    }
 
     fn total_pages(&self) -> usize {
         (self.end - self.start) / PAGE_SIZE
     }
 
+    /// 向上取整：`end` 不按页对齐时，`page_pos` 可能落在页中间，
+    /// 被部分占用的页也计为已使用。
     fn used_pages(&self) -> usize {
         let page_pos = self.page_pos.load(Ordering::SeqCst);
-        (self.end - page_pos) / PAGE_SIZE
+        (self.end - page_pos)
+            .div_ceil(PAGE_SIZE)
+            .min(self.total_pages())
     }
 
+    /// 保证 `used_pages + available_pages <= total_pages`
     fn available_pages(&self) -> usize {
         let byte_pos = self.byte_pos.load(Ordering::SeqCst);
         let page_pos = self.page_pos.load(Ordering::SeqCst);
-        if page_pos > byte_pos {
+        let gap = if page_pos > byte_pos {
             (page_pos - byte_pos) / PAGE_SIZE
         } else {
             0
-        }
+        };
+        gap.min(self.total_pages() - self.used_pages())
     }
 }
+
+#[cfg(test)]
+mod tests;
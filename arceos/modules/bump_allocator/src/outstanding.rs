@@ -0,0 +1,94 @@
+//! 调试构建下记录存活的字节分配，用于检测重复释放
+
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// 最多同时记录的存活字节分配数
+const SLOTS: usize = 64;
+
+/// 释放时查表的结果
+pub(crate) enum Removed {
+    /// 记录过的存活分配，`canary` 表示其末尾是否带越界哨兵
+    Recorded { canary: bool },
+    /// 表中没有，但有未记录的存活分配，无法判断
+    Unrecorded,
+    /// 可以确定不是存活分配
+    Missing,
+}
+
+/// 存活字节分配的起始地址表，0 表示空槽。
+///
+/// 同一地址可以出现多次（例如大小为 0 的分配），每次释放只移除一项。
+pub(crate) struct OutstandingTable {
+    slots: [AtomicUsize; SLOTS],
+    // 对应槽中的分配末尾是否带越界哨兵
+    canary: [AtomicBool; SLOTS],
+    // 有存活分配没有记录在表中（表满或导入的状态），此时无法判断重复释放
+    incomplete: AtomicBool,
+}
+
+impl OutstandingTable {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { AtomicUsize::new(0) }; SLOTS],
+            canary: [const { AtomicBool::new(false) }; SLOTS],
+            incomplete: AtomicBool::new(false),
+        }
+    }
+
+    /// 记录一个新的存活分配及其是否带哨兵，表满时改为标记为不完整
+    pub fn insert(&self, addr: usize, canary: bool) {
+        if addr != 0 {
+            for (slot, flag) in self.slots.iter().zip(&self.canary) {
+                if slot
+                    .compare_exchange(0, addr, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+                {
+                    flag.store(canary, Ordering::SeqCst);
+                    return;
+                }
+            }
+        }
+        self.mark_incomplete();
+    }
+
+    /// 移除 `addr` 的一项记录
+    pub fn remove(&self, addr: usize) -> Removed {
+        if addr != 0 {
+            for (slot, flag) in self.slots.iter().zip(&self.canary) {
+                // 先读标志：槽清空后可能立即被新的分配占用
+                let canary = flag.load(Ordering::SeqCst);
+                if slot
+                    .compare_exchange(addr, 0, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+                {
+                    return Removed::Recorded { canary };
+                }
+            }
+        }
+        if self.incomplete.load(Ordering::SeqCst) {
+            Removed::Unrecorded
+        } else {
+            Removed::Missing
+        }
+    }
+
+    /// 有未记录的存活分配，在下次清空前不再报告重复释放
+    pub fn mark_incomplete(&self) {
+        self.incomplete.store(true, Ordering::SeqCst);
+    }
+
+    /// 分配计数归零时调用：记录过的分配此时都已移除，未记录的也都已释放。
+    ///
+    /// 只清除不完整标记而不动表项，以免抹掉并发的新分配刚写入的记录。
+    pub fn all_freed(&self) {
+        self.incomplete.store(false, Ordering::SeqCst);
+    }
+
+    /// 所有分配都作废时（重置或重新初始化）清空
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            slot.store(0, Ordering::SeqCst);
+        }
+        self.incomplete.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,112 @@
+use allocator::{AllocError, AllocResult, BaseAllocator, ByteAllocator, PageAllocator};
+use core::alloc::Layout;
+use core::ptr::NonNull;
+use core::sync::atomic::Ordering;
+
+use crate::EarlyAllocator;
+
+/// 只做字节分配的子分配器，由 [`EarlyAllocator::split`] 得到
+pub struct ByteOnlyAllocator<const PAGE_SIZE: usize>(EarlyAllocator<PAGE_SIZE>);
+
+/// 只做页分配的子分配器，由 [`EarlyAllocator::split`] 得到
+pub struct PageOnlyAllocator<const PAGE_SIZE: usize>(EarlyAllocator<PAGE_SIZE>);
+
+impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
+    /// 在地址 `mid` 处把内存区域一分为二：`[start, mid)` 只做字节分配（向上推进），
+    /// `[mid, end)` 只做页分配（向下推进），两者互不争抢空间。
+    ///
+    /// `mid` 是固定的分界点，必须位于当前空闲区域 `[byte_pos, page_pos]` 内，
+    /// 已有的字节分配和页分配分别留在对应的一半中；否则返回 `InvalidParam`。
+    /// 页分配向下对齐，`mid` 不必按页对齐。
+    pub fn split(
+        self,
+        mid: usize,
+    ) -> AllocResult<(ByteOnlyAllocator<PAGE_SIZE>, PageOnlyAllocator<PAGE_SIZE>)> {
+        let byte_pos = self.byte_pos.load(Ordering::SeqCst);
+        let page_pos = self.page_pos.load(Ordering::SeqCst);
+        if !self.initialized || mid < byte_pos || mid > page_pos {
+            return Err(AllocError::InvalidParam);
+        }
+
+        let mut pages = Self::new();
+        pages.init(mid, self.end - mid);
+        pages.page_pos.store(page_pos, Ordering::SeqCst);
+        // 页的一半没有字节区，最低的页也可以分配
+        pages.page_only = true;
+
+        let mut bytes = self;
+        bytes.end = mid;
+        bytes.page_pos.store(mid, Ordering::SeqCst);
+
+        Ok((ByteOnlyAllocator(bytes), PageOnlyAllocator(pages)))
+    }
+}
+
+impl<const PAGE_SIZE: usize> BaseAllocator for ByteOnlyAllocator<PAGE_SIZE> {
+    fn init(&mut self, start: usize, size: usize) {
+        self.0.init(start, size);
+    }
+
+    fn add_memory(&mut self, _start: usize, _size: usize) -> AllocResult {
+        // 不支持
+        Err(AllocError::InvalidParam)
+    }
+}
+
+impl<const PAGE_SIZE: usize> ByteAllocator for ByteOnlyAllocator<PAGE_SIZE> {
+    /// 即使是整页的请求也从字节区分配
+    fn alloc(&mut self, layout: Layout) -> AllocResult<NonNull<u8>> {
+        self.0.alloc_bytes(layout)
+    }
+
+    fn dealloc(&mut self, pos: NonNull<u8>, layout: Layout) {
+        self.0.dealloc_bytes(pos, layout)
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.0.total_bytes()
+    }
+
+    fn used_bytes(&self) -> usize {
+        self.0.used_bytes()
+    }
+
+    fn available_bytes(&self) -> usize {
+        self.0.available_bytes()
+    }
+}
+
+impl<const PAGE_SIZE: usize> BaseAllocator for PageOnlyAllocator<PAGE_SIZE> {
+    fn init(&mut self, start: usize, size: usize) {
+        self.0.init(start, size);
+    }
+
+    fn add_memory(&mut self, _start: usize, _size: usize) -> AllocResult {
+        // 不支持
+        Err(AllocError::InvalidParam)
+    }
+}
+
+impl<const PAGE_SIZE: usize> PageAllocator for PageOnlyAllocator<PAGE_SIZE> {
+    const PAGE_SIZE: usize = PAGE_SIZE;
+
+    fn alloc_pages(&mut self, num_pages: usize, align_pow2: usize) -> AllocResult<usize> {
+        self.0.alloc_pages(num_pages, align_pow2)
+    }
+
+    fn dealloc_pages(&mut self, pos: usize, num_pages: usize) {
+        self.0.dealloc_pages(pos, num_pages)
+    }
+
+    fn total_pages(&self) -> usize {
+        self.0.total_pages()
+    }
+
+    fn used_pages(&self) -> usize {
+        self.0.used_pages()
+    }
+
+    fn available_pages(&self) -> usize {
+        self.0.available_pages()
+    }
+}
@@ -0,0 +1,61 @@
+use allocator::{AllocError, AllocResult, BaseAllocator};
+use core::sync::atomic::Ordering;
+
+use crate::EarlyAllocator;
+
+/// 可在启动阶段之间交接的分配器状态，由 [`EarlyAllocator::export_state`] 导出
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct EarlyAllocatorState {
+    pub start: usize,
+    pub end: usize,
+    pub byte_pos: usize,
+    pub page_pos: usize,
+    pub byte_count: usize,
+}
+
+impl EarlyAllocatorState {
+    /// 是否满足 `start <= byte_pos <= page_pos <= end`
+    pub fn is_valid(&self) -> bool {
+        self.start <= self.byte_pos && self.byte_pos <= self.page_pos && self.page_pos <= self.end
+    }
+}
+
+impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
+    /// 导出区域边界、两端位置和字节分配计数，供下一启动阶段用
+    /// [`import_state`](Self::import_state) 恢复
+    pub fn export_state(&self) -> EarlyAllocatorState {
+        EarlyAllocatorState {
+            start: self.start,
+            end: self.end,
+            byte_pos: self.byte_pos.load(Ordering::SeqCst),
+            page_pos: self.page_pos.load(Ordering::SeqCst),
+            byte_count: self.byte_count.load(Ordering::SeqCst),
+        }
+    }
+
+    /// 用导出的状态重新初始化分配器，如同在同一区域上 `init` 后完成了相同的分配。
+    ///
+    /// 状态不满足 `start <= byte_pos <= page_pos <= end` 时返回 `InvalidParam`
+    /// 且不做任何修改。导入的字节分配都视为位于回退下限之下，全部释放后
+    /// 字节区回到 `start`；`min_align` 与回收策略保持不变。
+    pub fn import_state(&mut self, state: EarlyAllocatorState) -> AllocResult {
+        if !state.is_valid() {
+            return Err(AllocError::InvalidParam);
+        }
+        let min_align = self.min_align;
+        self.init(state.start, state.end - state.start);
+        self.min_align = min_align;
+        self.byte_pos.store(state.byte_pos, Ordering::SeqCst);
+        self.page_pos.store(state.page_pos, Ordering::SeqCst);
+        self.byte_count.store(state.byte_count, Ordering::SeqCst);
+        self.reset_floor.store(state.byte_pos, Ordering::SeqCst);
+        self.floor_count.store(state.byte_count, Ordering::SeqCst);
+        // 导入的分配没有记录，释放它们不能被当作重复释放
+        #[cfg(debug_assertions)]
+        if state.byte_count != 0 {
+            self.outstanding.mark_incomplete();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,194 @@
+use allocator::{ByteAllocator, PageAllocator};
+use core::ops::Sub;
+
+use crate::EarlyAllocator;
+
+/// 分配器状态快照
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    pub total_bytes: usize,
+    pub used_bytes: usize,
+    pub available_bytes: usize,
+    pub total_pages: usize,
+    pub used_pages: usize,
+    pub available_pages: usize,
+}
+
+impl AllocStats {
+    const EMPTY: Self = Self {
+        total_bytes: 0,
+        used_bytes: 0,
+        available_bytes: 0,
+        total_pages: 0,
+        used_pages: 0,
+        available_pages: 0,
+    };
+}
+
+/// 两次快照之间使用量的变化，正数表示增加
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocDelta {
+    pub used_bytes: isize,
+    pub used_pages: isize,
+}
+
+impl AllocDelta {
+    /// 是否没有任何变化
+    pub fn is_zero(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// `after - before` 得到两次快照之间的变化
+impl Sub for AllocStats {
+    type Output = AllocDelta;
+
+    fn sub(self, before: Self) -> AllocDelta {
+        AllocDelta {
+            used_bytes: self.used_bytes.wrapping_sub(before.used_bytes) as isize,
+            used_pages: self.used_pages.wrapping_sub(before.used_pages) as isize,
+        }
+    }
+}
+
+/// 断言两次快照之间没有净分配，否则 panic 并给出详细的变化。
+///
+/// 用法：在被测代码前后各取一次 `stats()`，再调用本函数。
+#[track_caller]
+pub fn assert_no_leak(before: AllocStats, after: AllocStats) {
+    let delta = after - before;
+    if !delta.is_zero() {
+        panic!(
+            "allocation leak: used_bytes {:+} ({} -> {}), used_pages {:+} ({} -> {})",
+            delta.used_bytes,
+            before.used_bytes,
+            after.used_bytes,
+            delta.used_pages,
+            before.used_pages,
+            after.used_pages,
+        );
+    }
+}
+
+/// 把多个分配器的快照逐项相加，用于汇总多个内存池的使用情况
+pub fn combined_stats<const PAGE_SIZE: usize>(
+    allocators: &[&EarlyAllocator<PAGE_SIZE>],
+) -> AllocStats {
+    allocators
+        .iter()
+        .map(|a| a.stats())
+        .fold(AllocStats::EMPTY, |sum, s| AllocStats {
+            total_bytes: sum.total_bytes + s.total_bytes,
+            used_bytes: sum.used_bytes + s.used_bytes,
+            available_bytes: sum.available_bytes + s.available_bytes,
+            total_pages: sum.total_pages + s.total_pages,
+            used_pages: sum.used_pages + s.used_pages,
+            available_pages: sum.available_pages + s.available_pages,
+        })
+}
+
+impl<const PAGE_SIZE: usize> EarlyAllocator<PAGE_SIZE> {
+    /// 获取当前的使用情况快照
+    pub fn stats(&self) -> AllocStats {
+        AllocStats {
+            total_bytes: self.total_bytes(),
+            used_bytes: self.used_bytes(),
+            available_bytes: self.available_bytes(),
+            total_pages: self.total_pages(),
+            used_pages: self.used_pages(),
+            available_pages: self.available_pages(),
+        }
+    }
+
+    /// 同 `total_bytes`，未初始化时返回 `None`
+    pub fn try_total_bytes(&self) -> Option<usize> {
+        self.is_initialized().then(|| self.total_bytes())
+    }
+
+    /// 同 `used_bytes`，未初始化时返回 `None`
+    pub fn try_used_bytes(&self) -> Option<usize> {
+        self.is_initialized().then(|| self.used_bytes())
+    }
+
+    /// 同 `available_bytes`，未初始化时返回 `None`
+    pub fn try_available_bytes(&self) -> Option<usize> {
+        self.is_initialized().then(|| self.available_bytes())
+    }
+
+    /// 同 `total_pages`，未初始化时返回 `None`
+    pub fn try_total_pages(&self) -> Option<usize> {
+        self.is_initialized().then(|| self.total_pages())
+    }
+
+    /// 同 `used_pages`，未初始化时返回 `None`
+    pub fn try_used_pages(&self) -> Option<usize> {
+        self.is_initialized().then(|| self.used_pages())
+    }
+
+    /// 同 `available_pages`，未初始化时返回 `None`
+    pub fn try_available_pages(&self) -> Option<usize> {
+        self.is_initialized().then(|| self.available_pages())
+    }
+
+    /// 同 `stats`，未初始化时返回 `None`
+    pub fn try_stats(&self) -> Option<AllocStats> {
+        self.is_initialized().then(|| self.stats())
+    }
+}
+
+/// 按阶段记录分配器快照的定长历史，写满后丢弃最旧的记录
+pub struct HistoryRecorder<const N: usize> {
+    entries: [(&'static str, AllocStats); N],
+    // 最旧记录的下标
+    head: usize,
+    len: usize,
+}
+
+impl<const N: usize> HistoryRecorder<N> {
+    pub const fn new() -> Self {
+        Self {
+            entries: [("", AllocStats::EMPTY); N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// 以 `label` 记录 `allocator` 当前的快照
+    pub fn record<const PAGE_SIZE: usize>(
+        &mut self,
+        label: &'static str,
+        allocator: &EarlyAllocator<PAGE_SIZE>,
+    ) {
+        if N == 0 {
+            return;
+        }
+        let entry = (label, allocator.stats());
+        if self.len < N {
+            self.entries[(self.head + self.len) % N] = entry;
+            self.len += 1;
+        } else {
+            self.entries[self.head] = entry;
+            self.head = (self.head + 1) % N;
+        }
+    }
+
+    /// 记录条数
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 从旧到新遍历记录
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, AllocStats)> + '_ {
+        (0..self.len).map(move |i| self.entries[(self.head + i) % N])
+    }
+}
+
+impl<const N: usize> Default for HistoryRecorder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
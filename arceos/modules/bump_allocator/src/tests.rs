@@ -0,0 +1,1477 @@
+use allocator::{BaseAllocator, ByteAllocator, PageAllocator};
+
+use core::alloc::Layout;
+use core::ptr::NonNull;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::*;
+
+const PAGE_SIZE: usize = 4096;
+
+/// 基于一段假地址初始化分配器（不会真正访问内存）
+fn new_allocator(pages: usize) -> DefaultEarlyAllocator {
+    let mut a = DefaultEarlyAllocator::new();
+    a.init(0x8000_0000, pages * PAGE_SIZE);
+    a
+}
+
+/// 按页对齐、真实可写的测试内存区域
+#[repr(C, align(4096))]
+struct Arena<const PAGES: usize>([[u8; PAGE_SIZE]; PAGES]);
+
+impl<const PAGES: usize> Arena<PAGES> {
+    fn new(fill: u8) -> Box<Self> {
+        Box::new(Self([[fill; PAGE_SIZE]; PAGES]))
+    }
+
+    fn start(&self) -> usize {
+        self.0.as_ptr() as usize
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.0.as_flattened()
+    }
+
+    /// 在这块内存上初始化分配器
+    fn allocator(&mut self) -> DefaultEarlyAllocator {
+        let mut a = DefaultEarlyAllocator::new();
+        a.init(self.start(), PAGES * PAGE_SIZE);
+        a
+    }
+}
+
+#[test]
+fn default_alias_and_builder() {
+    let a = new_allocator(16);
+    assert_eq!(<DefaultEarlyAllocator as PageAllocator>::PAGE_SIZE, 4096);
+    assert_eq!(a.total_pages(), 16);
+    assert_eq!(a.total_bytes(), 16 * PAGE_SIZE);
+
+    let mut b = with_page_size::<0x200>();
+    b.init(0x8000_0000, 0x1000);
+    assert_eq!(b.total_pages(), 8);
+}
+
+#[test]
+fn lifo_churn_floors_at_pinned_block() {
+    let mut a = new_allocator(16);
+    let start = 0x8000_0000;
+    let pinned = Layout::from_size_align(96, 8).unwrap();
+    let small = Layout::from_size_align(32, 16).unwrap();
+
+    // 长期存活的分配，之后永不释放
+    let p = a.alloc(pinned).unwrap();
+    let pinned_end = p.as_ptr() as usize + 96;
+
+    // 按 LIFO 顺序释放
+    let b = a.alloc(small).unwrap();
+    let c = a.alloc(small).unwrap();
+    a.dealloc(c, small);
+    a.dealloc(b, small);
+    assert_eq!(a.used_bytes(), pinned_end - start);
+    assert_eq!(a.reset_floor(), pinned_end);
+
+    // 乱序释放：先释放下方的块，再释放顶端的块
+    let b = a.alloc(small).unwrap();
+    let c = a.alloc(small).unwrap();
+    a.dealloc(b, small);
+    assert!(a.used_bytes() > pinned_end - start);
+    a.dealloc(c, small);
+    assert_eq!(a.used_bytes(), pinned_end - start);
+
+    // 最后释放长期分配，整体重置
+    a.dealloc(p, pinned);
+    assert_eq!(a.used_bytes(), 0);
+    assert_eq!(a.reset_floor(), start);
+}
+
+#[test]
+fn reserved_region_survives_reset() {
+    let mut a = new_allocator(16);
+    let layout = Layout::from_size_align(64, 8).unwrap();
+
+    let x = a.alloc(layout).unwrap();
+    let table = a.reserve_bytes(4 * PAGE_SIZE, PAGE_SIZE).unwrap();
+    let table_end = table.as_ptr() as usize + 4 * PAGE_SIZE;
+    assert_eq!(table.as_ptr() as usize % PAGE_SIZE, 0);
+    assert_eq!(a.reserved_floor(), table_end);
+
+    let y = a.alloc(layout).unwrap();
+    assert!(y.as_ptr() as usize >= table_end);
+
+    // 释放全部普通分配，触发计数重置
+    a.dealloc(x, layout);
+    a.dealloc(y, layout);
+    assert_eq!(a.used_bytes(), table_end - 0x8000_0000);
+
+    let z = a.alloc(layout).unwrap();
+    assert_eq!(z.as_ptr() as usize, table_end);
+
+    assert_eq!(a.reserve_bytes(8, 3), Err(AllocError::InvalidParam));
+}
+
+#[test]
+fn alloc_from_either_end() {
+    let mut a = new_allocator(16);
+    let start = 0x8000_0000;
+    let end = start + 16 * PAGE_SIZE;
+    let layout = Layout::from_size_align(100, 8).unwrap();
+
+    let low = a.alloc_from(layout, End::Low).unwrap().as_ptr() as usize;
+    let high = a.alloc_from(layout, End::High).unwrap().as_ptr() as usize;
+    assert_eq!(low, start);
+    assert_eq!(high, end - PAGE_SIZE);
+    assert_eq!(a.used_bytes(), 100);
+    assert_eq!(a.used_pages(), 1);
+}
+
+#[test]
+fn pressure_callback_fires_once() {
+    static FIRED: AtomicUsize = AtomicUsize::new(0);
+    fn on_pressure() {
+        FIRED.fetch_add(1, Ordering::SeqCst);
+    }
+
+    let mut a = new_allocator(10);
+    a.set_pressure_callback(800, on_pressure);
+    let layout = Layout::from_size_align(PAGE_SIZE, 8).unwrap();
+
+    let mut blocks = [NonNull::dangling(); 9];
+    for (i, block) in blocks.iter_mut().enumerate() {
+        *block = a.alloc(layout).unwrap();
+        let expected = if i + 1 >= 8 { 1 } else { 0 };
+        assert_eq!(FIRED.load(Ordering::SeqCst), expected);
+    }
+
+    // 降回阈值以下后重新装填
+    for block in blocks.iter().rev().take(3) {
+        a.dealloc(*block, layout);
+    }
+    assert_eq!(a.byte_usage_permille(), 600);
+    a.alloc(layout).unwrap();
+    assert_eq!(FIRED.load(Ordering::SeqCst), 1);
+    a.alloc(layout).unwrap();
+    assert_eq!(FIRED.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn history_drops_oldest() {
+    let mut a = new_allocator(16);
+    let mut history = HistoryRecorder::<2>::new();
+    history.record("boot", &a);
+    a.alloc(Layout::from_size_align(64, 8).unwrap()).unwrap();
+    history.record("heap", &a);
+    a.alloc_pages(2, PAGE_SIZE).unwrap();
+    history.record("paging", &a);
+
+    assert_eq!(history.len(), 2);
+    let rows: Vec<_> = history
+        .iter()
+        .map(|(label, s)| (label, s.used_bytes, s.used_pages))
+        .collect();
+    assert_eq!(rows, [("heap", 64, 0), ("paging", 64, 2)]);
+}
+
+#[test]
+fn try_accessors_report_uninitialized() {
+    let mut a = DefaultEarlyAllocator::new();
+    assert!(!a.is_initialized());
+    assert_eq!(a.try_total_bytes(), None);
+    assert_eq!(a.try_used_bytes(), None);
+    assert_eq!(a.try_available_bytes(), None);
+    assert_eq!(a.try_total_pages(), None);
+    assert_eq!(a.try_used_pages(), None);
+    assert_eq!(a.try_available_pages(), None);
+    assert_eq!(a.try_stats(), None);
+
+    a.init(0x8000_0000, 4 * PAGE_SIZE);
+    assert!(a.is_initialized());
+    assert_eq!(a.try_total_bytes(), Some(4 * PAGE_SIZE));
+    assert_eq!(a.try_used_bytes(), Some(0));
+    assert_eq!(a.try_available_bytes(), Some(4 * PAGE_SIZE));
+    assert_eq!(a.try_total_pages(), Some(4));
+    assert_eq!(a.try_used_pages(), Some(0));
+    assert_eq!(a.try_available_pages(), Some(4));
+    assert_eq!(a.try_stats(), Some(a.stats()));
+}
+
+#[test]
+fn page_sized_alloc_uses_page_side() {
+    let mut a = new_allocator(16);
+    let page = Layout::from_size_align(PAGE_SIZE, PAGE_SIZE).unwrap();
+
+    let p = a.alloc(page).unwrap();
+    assert_eq!(p.as_ptr() as usize, 0x8000_0000 + 15 * PAGE_SIZE);
+    assert_eq!(a.used_pages(), 1);
+    assert_eq!(a.available_pages(), 15);
+    assert_eq!(a.used_bytes(), 0);
+
+    // 对齐不是整页的请求仍走字节区
+    let b = a
+        .alloc(Layout::from_size_align(PAGE_SIZE, 8).unwrap())
+        .unwrap();
+    assert_eq!(b.as_ptr() as usize, 0x8000_0000);
+    assert_eq!(a.used_bytes(), PAGE_SIZE);
+
+    a.dealloc(p, page);
+    assert_eq!(a.used_bytes(), PAGE_SIZE);
+}
+
+#[test]
+fn min_align_floor() {
+    let mut a = DefaultEarlyAllocator::new();
+    assert_eq!(
+        a.init_with_min_align(0x8000_0000, PAGE_SIZE, 6),
+        Err(AllocError::InvalidParam)
+    );
+    assert!(!a.is_initialized());
+
+    a.init_with_min_align(0x8000_0000, PAGE_SIZE, 8).unwrap();
+    let byte = Layout::from_size_align(1, 1).unwrap();
+    let x = a.alloc(byte).unwrap().as_ptr() as usize;
+    let y = a.alloc(byte).unwrap().as_ptr() as usize;
+    assert_eq!(y - x, 8);
+    assert_eq!(y % 8, 0);
+
+    // 更大的请求对齐优先
+    let z = a.alloc(Layout::from_size_align(1, 32).unwrap()).unwrap();
+    assert_eq!(z.as_ptr() as usize % 32, 0);
+}
+
+#[test]
+fn plan_layout_matches_allocations() {
+    let mut a = new_allocator(4);
+    a.alloc(Layout::from_size_align(3, 1).unwrap()).unwrap();
+
+    let reqs = [
+        Layout::from_size_align(10, 8).unwrap(),
+        Layout::from_size_align(1, 1).unwrap(),
+        Layout::from_size_align(64, 64).unwrap(),
+    ];
+    let base = 0x8000_0000 + a.used_bytes();
+    let (total, offsets) = a.plan_layout(&reqs).unwrap();
+    assert_eq!(offsets, [5, 15, 61]);
+    assert_eq!(total, 125);
+    assert_eq!(a.used_bytes(), 3);
+
+    for (layout, offset) in reqs.iter().zip(offsets) {
+        let p = a.alloc_from(*layout, End::Low).unwrap();
+        assert_eq!(p.as_ptr() as usize, base + offset);
+    }
+    assert_eq!(a.used_bytes(), 3 + total);
+
+    let huge = [Layout::from_size_align(4 * PAGE_SIZE, 8).unwrap()];
+    assert_eq!(a.plan_layout(&huge), Err(AllocError::NoMemory));
+}
+
+#[test]
+fn bump_bytes_is_contiguous() {
+    let mut a = new_allocator(1);
+    let start = 0x8000_0000;
+    assert_eq!(a.bump_bytes(3), Ok(start));
+    assert_eq!(a.bump_bytes(5), Ok(start + 3));
+    assert_eq!(a.bump_bytes(0), Ok(start + 8));
+    assert_eq!(a.used_bytes(), 8);
+
+    assert_eq!(a.bump_bytes(PAGE_SIZE), Err(AllocError::NoMemory));
+    assert_eq!(a.bump_bytes(usize::MAX), Err(AllocError::NoMemory));
+    assert_eq!(a.bump_bytes(PAGE_SIZE - 8), Ok(start + 8));
+    assert_eq!(a.available_bytes(), 0);
+}
+
+#[test]
+fn capacity_accounts_for_padding() {
+    let mut a = new_allocator(1);
+    a.bump_bytes(1).unwrap();
+
+    // 24 字节、按 16 对齐：每个实际占用 32 字节
+    let layout = Layout::from_size_align(24, 16).unwrap();
+    let naive = a.available_bytes() / 24;
+    let capacity = a.capacity_for(layout);
+    assert_eq!(naive, 170);
+    assert_eq!(capacity, 127);
+
+    for _ in 0..capacity {
+        a.alloc(layout).unwrap();
+    }
+    assert_eq!(a.alloc(layout), Err(AllocError::NoMemory));
+    assert_eq!(a.capacity_for(layout), 0);
+    assert_eq!(a.capacity_for(Layout::new::<()>()), usize::MAX);
+}
+
+#[test]
+fn carve_and_reclaim_child() {
+    let mut a = new_allocator(16);
+    let mut child = a.carve_child(4).unwrap();
+    assert_eq!(a.used_pages(), 4);
+    assert_eq!(child.total_pages(), 4);
+    child
+        .alloc(Layout::from_size_align(64, 8).unwrap())
+        .unwrap();
+
+    a.reclaim_child(child).unwrap();
+    assert_eq!(a.used_pages(), 0);
+
+    // 之后又分配过页的旧子分配器不能归还
+    let stale = a.carve_child(2).unwrap();
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(a.reclaim_child(stale), Err(AllocError::InvalidParam));
+
+    // 不是从这里切出的分配器也不能归还
+    let mut foreign = DefaultEarlyAllocator::new();
+    foreign.init(0x9000_0000, PAGE_SIZE);
+    assert_eq!(a.reclaim_child(foreign), Err(AllocError::InvalidParam));
+    assert_eq!(
+        a.reclaim_child(DefaultEarlyAllocator::new()),
+        Err(AllocError::InvalidParam)
+    );
+    assert_eq!(a.used_pages(), 3);
+
+    // 区域末尾不按页对齐：归还后连同对齐跳过的零头一起恢复
+    let mut a = DefaultEarlyAllocator::new();
+    a.init(0x8000_0000, 4 * PAGE_SIZE + 100);
+    a.alloc(Layout::from_size_align(10, 1).unwrap()).unwrap();
+    let before = (a.used_pages(), a.available_pages(), a.available_bytes());
+    let child = a.carve_child(2).unwrap();
+    assert_eq!(a.page_alignment_waste(), 100);
+    a.reclaim_child(child).unwrap();
+    assert_eq!(
+        (a.used_pages(), a.available_pages(), a.available_bytes()),
+        before
+    );
+    assert_eq!(a.used_pages(), 0);
+    assert_eq!(a.page_alignment_waste(), 0);
+}
+
+#[test]
+fn reclaim_rejects_forged_child() {
+    let mut a = new_allocator(16);
+    let low = a.alloc_pages(2, PAGE_SIZE).unwrap();
+    let child = a.carve_child(2).unwrap();
+    let start = a.page_base(12).unwrap();
+
+    // 起始地址相同但更长，会越过切出之前分配的页块
+    let mut long = DefaultEarlyAllocator::new();
+    long.init(start, 4 * PAGE_SIZE);
+    assert_eq!(a.reclaim_child(long), Err(AllocError::InvalidParam));
+
+    // 范围完全相同但手工构造的分配器同样不能冒充：归还一次后记录即被清除
+    let mut forged = DefaultEarlyAllocator::new();
+    forged.init(start, 2 * PAGE_SIZE);
+    a.reclaim_child(child).unwrap();
+    assert_eq!(a.reclaim_child(forged), Err(AllocError::InvalidParam));
+    assert_eq!(a.used_pages(), 2);
+    assert_eq!(
+        a.is_page_allocated(a.page_index_of(low).unwrap()),
+        Some(true)
+    );
+}
+
+#[test]
+fn zero_free_region_spares_live_blocks() {
+    let mut arena = Arena::<4>::new(0xff);
+    let mut a = arena.allocator();
+    let byte = a.alloc(Layout::from_size_align(100, 8).unwrap()).unwrap();
+    let page = a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(byte.as_ptr() as usize, arena.start());
+    assert_eq!(page, arena.start() + 3 * PAGE_SIZE);
+
+    unsafe {
+        a.zero_free_region();
+        a.zero_free_region();
+    }
+    let bytes = arena.bytes();
+    assert!(bytes[..100].iter().all(|&b| b == 0xff));
+    assert!(bytes[100..3 * PAGE_SIZE].iter().all(|&b| b == 0));
+    assert!(bytes[3 * PAGE_SIZE..].iter().all(|&b| b == 0xff));
+}
+
+#[test]
+fn concurrent_alloc_and_reset() {
+    const THREADS: usize = 4;
+    const ROUNDS: usize = 10_000;
+
+    let mut arena = Arena::<16>::new(0);
+    let a = arena.allocator();
+    let layout = Layout::from_size_align(48, 16).unwrap();
+    std::thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|| {
+                for _ in 0..ROUNDS {
+                    // 直接调用 &self 的内部路径，模拟并发的分配与释放；
+                    // 回退被抢先时会暂时浪费空间，因此允许分配失败
+                    let Ok(p) = a.alloc_bytes(layout) else {
+                        assert!(a.check_invariants());
+                        continue;
+                    };
+                    assert!(a.check_invariants());
+                    unsafe { p.as_ptr().write_bytes(0xa5, layout.size()) };
+                    a.dealloc_bytes(p, layout);
+                    assert!(a.check_invariants());
+                }
+            });
+        }
+    });
+    assert!(a.check_invariants());
+}
+
+#[test]
+fn alloc_ids() {
+    let mut a = new_allocator(4);
+    assert_eq!(a.next_alloc_id(), 0);
+
+    let small = Layout::from_size_align(24, 8).unwrap();
+    let p = a.alloc(small).unwrap();
+    assert_eq!(a.next_alloc_id(), 1);
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(a.next_alloc_id(), 2);
+    // 整页请求走页区，也只算一次分配
+    a.alloc(Layout::from_size_align(PAGE_SIZE, PAGE_SIZE).unwrap())
+        .unwrap();
+    assert_eq!(a.next_alloc_id(), 3);
+
+    // 失败的分配不消耗 ID
+    assert_eq!(a.alloc_pages(8, PAGE_SIZE), Err(AllocError::NoMemory));
+    assert!(a
+        .alloc(Layout::from_size_align(8 * PAGE_SIZE, 8).unwrap())
+        .is_err());
+    assert_eq!(a.next_alloc_id(), 3);
+
+    // 释放与重置都不会让 ID 回退
+    a.dealloc(p, small);
+    a.alloc(small).unwrap();
+    assert_eq!(a.next_alloc_id(), 4);
+}
+
+#[test]
+fn freeze_blocks_allocation() {
+    let mut a = new_allocator(4);
+    let layout = Layout::from_size_align(32, 8).unwrap();
+    let p = a.alloc(layout).unwrap();
+    let id = a.next_alloc_id();
+
+    a.freeze();
+    assert!(a.is_frozen());
+    assert_eq!(a.alloc(layout), Err(AllocError::NoMemory));
+    assert_eq!(a.alloc_pages(1, PAGE_SIZE), Err(AllocError::NoMemory));
+    assert_eq!(a.bump_bytes(8), Err(AllocError::NoMemory));
+    assert_eq!(a.next_alloc_id(), id);
+
+    // 冻结期间仍可释放
+    a.dealloc(p, layout);
+    assert_eq!(a.used_bytes(), 0);
+
+    a.unfreeze();
+    assert!(!a.is_frozen());
+    a.alloc(layout).unwrap();
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+}
+
+#[test]
+fn page_index_round_trip() {
+    let a = new_allocator(4);
+    let start = 0x8000_0000;
+
+    assert_eq!(a.page_index_of(start), Some(0));
+    assert_eq!(a.page_index_of(start + PAGE_SIZE - 1), Some(0));
+    assert_eq!(a.page_index_of(start + 3 * PAGE_SIZE + 100), Some(3));
+    assert_eq!(a.page_index_of(start - 1), None);
+    assert_eq!(a.page_index_of(start + 4 * PAGE_SIZE), None);
+
+    for index in 0..4 {
+        let base = a.page_base(index).unwrap();
+        assert_eq!(base, start + index * PAGE_SIZE);
+        assert_eq!(a.page_index_of(base), Some(index));
+    }
+    assert_eq!(a.page_base(4), None);
+    assert_eq!(a.page_base(usize::MAX), None);
+}
+
+#[test]
+fn page_ownership() {
+    let mut a = new_allocator(8);
+    let p0 = a.alloc_pages(1, PAGE_SIZE).unwrap();
+    let p1 = a.alloc_pages(2, PAGE_SIZE).unwrap();
+    let p2 = a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(a.page_index_of(p0), Some(7));
+    assert_eq!(a.page_index_of(p1), Some(5));
+    assert_eq!(a.page_index_of(p2), Some(4));
+
+    // 释放最近的页块后其页变为未分配
+    a.dealloc_pages(p2, 1);
+    assert_eq!(a.is_page_allocated(4), Some(false));
+    assert_eq!(a.used_pages(), 3);
+
+    assert_eq!(a.is_page_allocated(5), Some(true));
+    assert_eq!(a.is_page_allocated(6), Some(true));
+    assert_eq!(a.is_page_allocated(7), Some(true));
+    assert_eq!(a.is_page_allocated(0), Some(false));
+    assert_eq!(a.is_page_allocated(8), None);
+}
+
+#[test]
+fn freed_non_top_page_stays_allocated() {
+    let mut a = new_allocator(8);
+    let low = a.alloc_pages(1, PAGE_SIZE).unwrap();
+    let top = a.alloc_pages(1, PAGE_SIZE).unwrap();
+
+    // 释放非顶端的页块不会移动 p_pos
+    a.dealloc_pages(low, 1);
+    assert_eq!(a.is_page_allocated(7), Some(true));
+
+    // 顶端页块归还后只有它变为未分配
+    a.dealloc_pages(top, 1);
+    assert_eq!(a.is_page_allocated(6), Some(false));
+    assert_eq!(a.is_page_allocated(7), Some(true));
+}
+
+#[test]
+fn contiguous_uses_whole_arena() {
+    let mut a = new_allocator(4);
+    let p = a.alloc_contiguous(4 * PAGE_SIZE, 16).unwrap();
+    assert_eq!(p.as_ptr() as usize, 0x8000_0000);
+    assert_eq!(a.available_bytes(), 0);
+    assert_eq!(a.alloc_pages(1, PAGE_SIZE), Err(AllocError::NoMemory));
+
+    a.dealloc(p, Layout::from_size_align(4 * PAGE_SIZE, 16).unwrap());
+    assert_eq!(a.available_pages(), 4);
+}
+
+#[test]
+fn contiguous_page_aligned_takes_whole_arena() {
+    let mut a = new_allocator(1);
+    let layout = Layout::from_size_align(PAGE_SIZE, PAGE_SIZE).unwrap();
+    let p = a.alloc_contiguous(PAGE_SIZE, PAGE_SIZE).unwrap();
+    assert_eq!(p.as_ptr() as usize, 0x8000_0000);
+    assert_eq!(a.used_bytes(), PAGE_SIZE);
+    assert_eq!(a.used_pages(), 0);
+
+    // 整页布局释放时仍回到字节区
+    a.dealloc(p, layout);
+    assert_eq!(a.used_bytes(), 0);
+    assert_eq!(a.alloc_contiguous(PAGE_SIZE, PAGE_SIZE), Ok(p));
+}
+
+#[test]
+fn contiguous_rejects_outstanding_allocations() {
+    let mut a = new_allocator(4);
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(a.alloc_contiguous(PAGE_SIZE, 8), Err(AllocError::NoMemory));
+
+    let mut a = new_allocator(4);
+    a.alloc(Layout::from_size_align(8, 8).unwrap()).unwrap();
+    assert_eq!(a.alloc_contiguous(PAGE_SIZE, 8), Err(AllocError::NoMemory));
+
+    let mut a = new_allocator(4);
+    a.reserve_bytes(8, 8).unwrap();
+    assert_eq!(a.alloc_contiguous(PAGE_SIZE, 8), Err(AllocError::NoMemory));
+
+    let mut a = new_allocator(4);
+    assert_eq!(
+        a.alloc_contiguous(5 * PAGE_SIZE, 8),
+        Err(AllocError::NoMemory)
+    );
+}
+
+#[test]
+fn largest_free_block_is_shared_gap() {
+    let mut a = new_allocator(8);
+    assert_eq!(a.largest_free_block(), 8 * PAGE_SIZE);
+
+    a.alloc(Layout::from_size_align(100, 8).unwrap()).unwrap();
+    assert_eq!(a.largest_free_block(), 8 * PAGE_SIZE - 100);
+    assert_eq!(a.largest_free_block(), a.available_bytes());
+
+    a.alloc_pages(2, PAGE_SIZE).unwrap();
+    assert_eq!(a.largest_free_block(), 6 * PAGE_SIZE - 100);
+
+    a.alloc(Layout::from_size_align(28, 4).unwrap()).unwrap();
+    assert_eq!(a.largest_free_block(), 6 * PAGE_SIZE - 128);
+    assert_eq!(a.largest_free_block(), a.available_bytes());
+}
+
+#[test]
+fn overlapping_arenas() {
+    let arena = |start: usize, pages: usize| {
+        let mut a = DefaultEarlyAllocator::new();
+        a.init(start, pages * PAGE_SIZE);
+        a
+    };
+    let a = arena(0x8000_0000, 4);
+
+    // 重叠
+    assert!(arenas_overlap(&a, &arena(0x8000_3000, 4)));
+    assert!(arenas_overlap(&arena(0x7fff_f000, 2), &a));
+    assert!(arenas_overlap(&a, &arena(0x8000_1000, 1)));
+    // 相邻
+    assert!(!arenas_overlap(&a, &arena(0x8000_4000, 4)));
+    assert!(!arenas_overlap(&arena(0x7fff_c000, 4), &a));
+    // 不相交
+    assert!(!arenas_overlap(&a, &arena(0x9000_0000, 4)));
+    assert!(!arenas_overlap(&a, &arena(usize::MAX - PAGE_SIZE + 1, 0)));
+}
+
+#[test]
+fn dealloc_top_page_block() {
+    let mut a = new_allocator(8);
+    let low = a.alloc_pages(2, PAGE_SIZE).unwrap();
+    let top = a.alloc_pages(3, PAGE_SIZE).unwrap();
+    assert_eq!(a.available_pages(), 3);
+
+    // 非顶端的释放不做任何事
+    a.dealloc_pages(low, 2);
+    assert_eq!(a.available_pages(), 3);
+
+    // 顶端页块可以回收
+    a.dealloc_pages(top, 3);
+    assert_eq!(a.available_pages(), 6);
+    assert_eq!(a.alloc_pages(3, PAGE_SIZE), Ok(top));
+}
+
+#[test]
+fn reset_transient_keeps_reserved() {
+    let mut a = new_allocator(4);
+    let layout = Layout::from_size_align(64, 8).unwrap();
+    a.alloc(layout).unwrap();
+    let pt = a.reserve_bytes(PAGE_SIZE, PAGE_SIZE).unwrap();
+    let reserved_end = pt.as_ptr() as usize + PAGE_SIZE;
+    a.alloc(layout).unwrap();
+    a.alloc(layout).unwrap();
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+
+    a.reset_transient();
+    assert_eq!(a.reserved_floor(), reserved_end);
+    assert_eq!(a.used_bytes(), reserved_end - 0x8000_0000);
+    assert_eq!(a.used_pages(), 1);
+
+    // 临时空间被回收，新分配紧接在保留区域之后
+    let p = a.alloc(layout).unwrap();
+    assert_eq!(p.as_ptr() as usize, reserved_end);
+    a.dealloc(p, layout);
+    assert_eq!(a.used_bytes(), reserved_end - 0x8000_0000);
+}
+
+/// 分配两块再按相反顺序释放，净分配为零
+fn balanced(a: &mut DefaultEarlyAllocator) {
+    let layout = Layout::from_size_align(40, 8).unwrap();
+    let p = a.alloc(layout).unwrap();
+    let q = a.alloc(layout).unwrap();
+    a.dealloc(q, layout);
+    a.dealloc(p, layout);
+}
+
+/// 留下一块字节分配和一页
+fn leaky(a: &mut DefaultEarlyAllocator) {
+    a.alloc(Layout::from_size_align(40, 8).unwrap()).unwrap();
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+}
+
+#[test]
+fn no_leak_when_balanced() {
+    let mut a = new_allocator(4);
+    a.alloc(Layout::from_size_align(16, 8).unwrap()).unwrap();
+    let before = a.stats();
+    balanced(&mut a);
+    let after = a.stats();
+    assert_eq!(before, after);
+    assert!((after - before).is_zero());
+    assert_no_leak(before, after);
+}
+
+#[test]
+#[should_panic(expected = "allocation leak: used_bytes +40 (16 -> 56), used_pages +1 (0 -> 1)")]
+fn leak_is_reported() {
+    let mut a = new_allocator(4);
+    a.alloc(Layout::from_size_align(16, 8).unwrap()).unwrap();
+    let before = a.stats();
+    leaky(&mut a);
+    assert_eq!(
+        a.stats() - before,
+        AllocDelta {
+            used_bytes: 40,
+            used_pages: 1
+        }
+    );
+    assert_no_leak(before, a.stats());
+}
+
+#[test]
+fn combined_stats_sums_instances() {
+    let mut a = new_allocator(4);
+    let mut b = new_allocator(16);
+    a.alloc(Layout::from_size_align(24, 8).unwrap()).unwrap();
+    b.alloc(Layout::from_size_align(100, 4).unwrap()).unwrap();
+    b.alloc_pages(2, PAGE_SIZE).unwrap();
+
+    let (sa, sb) = (a.stats(), b.stats());
+    let sum = combined_stats(&[&a, &b]);
+    assert_eq!(sum.total_bytes, 20 * PAGE_SIZE);
+    assert_eq!(sum.total_bytes, sa.total_bytes + sb.total_bytes);
+    assert_eq!(sum.used_bytes, sa.used_bytes + sb.used_bytes);
+    assert_eq!(sum.available_bytes, sa.available_bytes + sb.available_bytes);
+    assert_eq!(sum.total_pages, 20);
+    assert_eq!(sum.used_pages, 2);
+    assert_eq!(sum.available_pages, sa.available_pages + sb.available_pages);
+
+    assert_eq!(combined_stats(&[&a]), sa);
+    assert_eq!(combined_stats::<PAGE_SIZE>(&[]), AllocStats::default());
+}
+
+#[cfg(feature = "track")]
+#[test]
+fn alignment_histogram_buckets_padding() {
+    let mut a = new_allocator(4);
+    // (size, align, 插入的填充)
+    let reqs = [
+        (1, 1, 0),
+        (8, 8, 7),
+        (1, 1, 0),
+        (4, 2, 1),
+        (1, 64, 42),
+        (1, 4096, 4031),
+        (2, 2, 1),
+    ];
+    for (size, align, padding) in reqs {
+        a.alloc(Layout::from_size_align(size, align).unwrap())
+            .unwrap();
+        assert_eq!(a.last_allocation_padding(), padding);
+    }
+    let mut expected = [0; PADDING_BUCKETS];
+    expected[0] = 2;
+    expected[2] = 1;
+    expected[5] = 1;
+    expected[11] = 1;
+    assert_eq!(a.alignment_histogram(), expected);
+
+    // 失败的分配不计入
+    a.alloc(Layout::from_size_align(1, 1 << 20).unwrap())
+        .unwrap_err();
+    assert_eq!(a.alignment_histogram(), expected);
+
+    // 随字节区一起清零
+    a.reset_transient();
+    assert_eq!(a.alignment_histogram(), [0; PADDING_BUCKETS]);
+    a.alloc(Layout::from_size_align(1, 1).unwrap()).unwrap();
+    a.alloc(Layout::from_size_align(1, 16).unwrap()).unwrap();
+    expected = [0; PADDING_BUCKETS];
+    expected[3] = 1;
+    assert_eq!(a.alignment_histogram(), expected);
+    a.init(0x8000_0000, 4 * PAGE_SIZE);
+    assert_eq!(a.alignment_histogram(), [0; PADDING_BUCKETS]);
+}
+
+/// 按给定策略分配三块，依次释放最顶端的两块，再释放最后一块，
+/// 记录每一步之后的 `used_bytes`
+fn reclaim_trace(policy: ReclaimPolicy) -> [usize; 3] {
+    let mut a = new_allocator(4);
+    a.set_reclaim_policy(policy);
+    let layout = Layout::from_size_align(32, 8).unwrap();
+    let p = a.alloc(layout).unwrap();
+    let q = a.alloc(layout).unwrap();
+    let r = a.alloc(layout).unwrap();
+    a.dealloc(r, layout);
+    let after_r = a.used_bytes();
+    a.dealloc(q, layout);
+    let after_q = a.used_bytes();
+    a.dealloc(p, layout);
+    [after_r, after_q, a.used_bytes()]
+}
+
+#[test]
+fn reclaim_policies() {
+    assert_eq!(
+        DefaultEarlyAllocator::new().reclaim_policy(),
+        ReclaimPolicy::Lifo
+    );
+    assert_eq!(reclaim_trace(ReclaimPolicy::Never), [96, 96, 96]);
+    assert_eq!(reclaim_trace(ReclaimPolicy::CountReset), [96, 96, 0]);
+    assert_eq!(reclaim_trace(ReclaimPolicy::Lifo), [64, 32, 0]);
+}
+
+#[test]
+fn used_byte_region_reflects_writes() {
+    let mut arena = Arena::<2>::new(0);
+    let mut a = arena.allocator();
+    assert!(unsafe { a.used_byte_region() }.is_empty());
+
+    let p = a.alloc(Layout::from_size_align(3, 1).unwrap()).unwrap();
+    let q = a.alloc(Layout::from_size_align(4, 4).unwrap()).unwrap();
+    unsafe {
+        p.as_ptr().copy_from(b"abc".as_ptr(), 3);
+        q.as_ptr().copy_from(b"wxyz".as_ptr(), 4);
+        // 第二块前有 1 字节对齐填充
+        assert_eq!(a.used_byte_region(), b"abc\0wxyz");
+    }
+}
+
+#[test]
+fn page_counts_round_up_mid_page() {
+    // 结束地址不按页对齐，页分配向下对齐后 page_pos 离 end 不是整页
+    let mut a = DefaultEarlyAllocator::new();
+    a.init(0x8000_0000, 4 * PAGE_SIZE + PAGE_SIZE / 2);
+    assert_eq!(a.total_pages(), 4);
+
+    let p = a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(p, 0x8000_3000);
+    assert_eq!(a.used_pages(), 2);
+    assert_eq!(a.available_pages(), 2);
+
+    a.alloc(Layout::from_size_align(PAGE_SIZE / 2, 8).unwrap())
+        .unwrap();
+    assert_eq!(a.available_pages(), 2);
+
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(a.used_pages() + a.available_pages(), 4);
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(a.used_pages(), 4);
+    assert_eq!(a.available_pages(), 0);
+}
+
+#[test]
+fn last_allocation_padding() {
+    let mut a = new_allocator(4);
+    let byte = Layout::from_size_align(1, 1).unwrap();
+    let wide = Layout::from_size_align(8, 64).unwrap();
+
+    let p = a.alloc(byte).unwrap();
+    assert_eq!(a.last_allocation_padding(), 0);
+    let q = a.alloc(wide).unwrap();
+    assert_eq!(a.last_allocation_padding(), 63);
+    assert_eq!(q.as_ptr() as usize - p.as_ptr() as usize - 1, 63);
+
+    // 整体重置后归零
+    a.dealloc(q, wide);
+    a.dealloc(p, byte);
+    assert_eq!(a.last_allocation_padding(), 0);
+}
+
+#[test]
+fn packable_count_matches_trial_allocation() {
+    for (size, align) in [(1, 1), (24, 16), (100, 64), (3000, 8)] {
+        let layout = Layout::from_size_align(size, align).unwrap();
+        let mut a = new_allocator(4);
+        // 让起点不落在对齐位置上
+        a.alloc(Layout::from_size_align(3, 1).unwrap()).unwrap();
+
+        let expected = a.packable_count(layout);
+        let mut allocated = 0;
+        while a.alloc(layout).is_ok() {
+            allocated += 1;
+        }
+        assert_eq!(allocated, expected, "layout {:?}", layout);
+    }
+}
+
+#[test]
+fn packable_count_follows_alloc_routing() {
+    // 整页请求由 alloc 转到页区，按剩余的页数计
+    let page = Layout::from_size_align(PAGE_SIZE, PAGE_SIZE).unwrap();
+    let mut a = new_allocator(4);
+    a.alloc(Layout::from_size_align(3, 1).unwrap()).unwrap();
+    let expected = a.packable_count(page);
+    assert_eq!(expected, 3);
+    let mut allocated = 0;
+    while a.alloc(page).is_ok() {
+        allocated += 1;
+    }
+    assert_eq!(allocated, expected);
+
+    a.freeze();
+    assert_eq!(a.packable_count(Layout::new::<u8>()), 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn packable_count_includes_canaries() {
+    let mut arena = Arena::<1>::new(0);
+    let mut a = arena.allocator();
+    a.enable_canaries();
+    let layout = Layout::from_size_align(24, 8).unwrap();
+
+    let expected = a.packable_count(layout);
+    assert!(expected < a.capacity_for(layout));
+    let mut allocated = 0;
+    while a.alloc(layout).is_ok() {
+        allocated += 1;
+    }
+    assert_eq!(allocated, expected);
+}
+
+#[test]
+fn page_aligned_buffer_from_page_side() {
+    let mut a = new_allocator(8);
+    a.alloc(Layout::from_size_align(10, 1).unwrap()).unwrap();
+
+    let buf = a.alloc_page_aligned(PAGE_SIZE + 1).unwrap();
+    let addr = buf.as_ptr() as usize;
+    assert_eq!(addr % PAGE_SIZE, 0);
+    assert_eq!(addr, 0x8000_0000 + 6 * PAGE_SIZE);
+    assert_eq!(a.used_pages(), 2);
+    // 字节区没有产生填充
+    assert_eq!(a.used_bytes(), 10);
+    assert_eq!(a.alloc_page_aligned(0), Err(AllocError::InvalidParam));
+}
+
+#[test]
+fn alloc_pow2_rounds_size_and_align() {
+    let mut a = new_allocator(4);
+    a.alloc(Layout::from_size_align(1, 1).unwrap()).unwrap();
+
+    // 3 取整为 4，并按 4 对齐
+    let p = a.alloc_pow2(3).unwrap().as_ptr() as usize;
+    assert_eq!(p, 0x8000_0004);
+    assert_eq!(a.used_bytes(), 8);
+
+    // 8 已经是 2 的幂，保持不变
+    let q = a.alloc_pow2(8).unwrap().as_ptr() as usize;
+    assert_eq!(q, 0x8000_0008);
+    assert_eq!(q % 8, 0);
+    assert_eq!(a.used_bytes(), 16);
+
+    a.dealloc(
+        NonNull::new(q as *mut u8).unwrap(),
+        Layout::from_size_align(8, 8).unwrap(),
+    );
+    assert_eq!(a.used_bytes(), 8);
+    assert_eq!(a.alloc_pow2(0), Err(AllocError::InvalidParam));
+    assert_eq!(a.alloc_pow2(usize::MAX), Err(AllocError::NoMemory));
+}
+
+#[test]
+fn page_handles_free_in_reverse() {
+    let mut a = new_allocator(8);
+    let h1 = a.alloc_pages_handle(2, PAGE_SIZE).unwrap();
+    let h2 = a.alloc_pages_handle(1, PAGE_SIZE).unwrap();
+    assert_eq!(h1.base(), 0x8000_0000 + 6 * PAGE_SIZE);
+    assert_eq!(h1.num_pages(), 2);
+    assert_eq!(h2.base(), 0x8000_0000 + 5 * PAGE_SIZE);
+    assert_eq!(a.used_pages(), 3);
+
+    a.free_handle(h2);
+    assert_eq!(a.used_pages(), 2);
+    a.free_handle(h1);
+    assert_eq!(a.used_pages(), 0);
+    assert_eq!(a.available_pages(), 8);
+
+    // 不是最近一次分配的页块不会被回收
+    let h1 = a.alloc_pages_handle(1, PAGE_SIZE).unwrap();
+    let h2 = a.alloc_pages_handle(1, PAGE_SIZE).unwrap();
+    a.free_handle(h1);
+    assert_eq!(a.used_pages(), 2);
+    a.free_handle(h2);
+    assert_eq!(a.used_pages(), 1);
+}
+
+#[test]
+fn split_into_disjoint_halves() {
+    let mut a = new_allocator(8);
+    let small = Layout::from_size_align(100, 8).unwrap();
+    a.alloc(small).unwrap();
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+
+    let mid = 0x8000_0000 + 3 * PAGE_SIZE;
+    let (mut bytes, mut pages) = a.split(mid).unwrap();
+    assert_eq!(bytes.total_bytes(), 3 * PAGE_SIZE);
+    assert_eq!(pages.total_pages(), 5);
+    assert_eq!(
+        bytes.total_bytes() + pages.total_pages() * PAGE_SIZE,
+        8 * PAGE_SIZE
+    );
+    // 已有的分配留在各自的一半中
+    assert_eq!(bytes.used_bytes(), 100);
+    assert_eq!(pages.used_pages(), 1);
+
+    // 整页请求也留在字节的一半中
+    let p = bytes
+        .alloc(Layout::from_size_align(PAGE_SIZE, PAGE_SIZE).unwrap())
+        .unwrap();
+    assert!((p.as_ptr() as usize) < mid);
+    // 字节的一半恰好在 mid 处用尽，不会越界到页的一半
+    let q = bytes
+        .alloc(Layout::from_size_align(PAGE_SIZE, 8).unwrap())
+        .unwrap();
+    assert_eq!(q.as_ptr() as usize + PAGE_SIZE, mid);
+    assert_eq!(
+        bytes.alloc(Layout::from_size_align(1, 1).unwrap()),
+        Err(AllocError::NoMemory)
+    );
+
+    while let Ok(page) = pages.alloc_pages(1, PAGE_SIZE) {
+        assert!(page >= mid);
+    }
+}
+
+#[test]
+fn split_page_half_uses_every_page() {
+    let a = new_allocator(8);
+    let mid = 0x8000_0000 + 4 * PAGE_SIZE;
+    let (_bytes, mut pages) = a.split(mid).unwrap();
+    assert_eq!(pages.available_pages(), 4);
+
+    // 报告可用的页都能分配出来，最低一页恰好从 mid 开始
+    assert_eq!(pages.alloc_pages(4, PAGE_SIZE), Ok(mid));
+    assert_eq!(pages.available_pages(), 0);
+    assert_eq!(pages.alloc_pages(1, PAGE_SIZE), Err(AllocError::NoMemory));
+
+    pages.dealloc_pages(mid, 4);
+    for i in (0..4).rev() {
+        assert_eq!(pages.alloc_pages(1, PAGE_SIZE), Ok(mid + i * PAGE_SIZE));
+    }
+}
+
+#[test]
+fn split_point_must_be_free() {
+    let mut a = new_allocator(8);
+    a.alloc(Layout::from_size_align(PAGE_SIZE, 8).unwrap())
+        .unwrap();
+    assert!(a.split(0x8000_0000).is_err());
+
+    let mut a = new_allocator(8);
+    a.alloc_pages(2, PAGE_SIZE).unwrap();
+    assert!(a.split(0x8000_0000 + 7 * PAGE_SIZE).is_err());
+
+    assert!(DefaultEarlyAllocator::new().split(0).is_err());
+}
+
+#[test]
+fn page_alignment_waste() {
+    let mut a = new_allocator(16);
+    a.alloc_pages(1, PAGE_SIZE).unwrap();
+    assert_eq!(a.page_alignment_waste(), 0);
+
+    // 剩余 15 页，按 4 页对齐分配 1 页会跳过 2 页
+    let p = a.alloc_pages(1, 4 * PAGE_SIZE).unwrap();
+    assert_eq!(p, 0x8000_0000 + 12 * PAGE_SIZE);
+    assert_eq!(a.page_alignment_waste(), 2 * PAGE_SIZE);
+    assert_eq!(a.used_pages(), 4);
+
+    a.alloc_pages(2, PAGE_SIZE).unwrap();
+    assert_eq!(a.page_alignment_waste(), 2 * PAGE_SIZE);
+}
+
+#[test]
+fn total_bytes_checked() {
+    let mut a = new_allocator(4);
+    assert_eq!(a.total_bytes_checked(), Some(4 * PAGE_SIZE));
+
+    // 人为破坏状态：end 落在 start 之前
+    a.end = a.start - PAGE_SIZE;
+    assert_eq!(a.total_bytes_checked(), None);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn canaries_keep_lifo_reclaim() {
+    let mut arena = Arena::<1>::new(0);
+    let mut a = arena.allocator();
+    a.enable_canaries();
+    let layout = Layout::from_size_align(16, 8).unwrap();
+
+    let p = a.alloc(layout).unwrap();
+    let q = a.alloc(layout).unwrap();
+    assert_eq!(q.as_ptr() as usize, p.as_ptr() as usize + 24);
+    unsafe { p.as_ptr().write_bytes(0xff, 16) };
+
+    // 哨兵完好时照常释放并按 LIFO 回退
+    a.dealloc(q, layout);
+    assert_eq!(a.used_bytes(), 24);
+    a.dealloc(p, layout);
+    assert_eq!(a.used_bytes(), 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn bump_bytes_free_skips_canary() {
+    let mut arena = Arena::<1>::new(0);
+    let mut a = arena.allocator();
+    a.set_reclaim_policy(ReclaimPolicy::Lifo);
+    a.enable_canaries();
+    let layout = Layout::from_size_align(16, 8).unwrap();
+
+    // bump_bytes 的区域不带哨兵，释放时既不检查，也按其实际末尾弹回
+    let p = a.alloc(layout).unwrap();
+    let q = a.bump_bytes(5).unwrap();
+    assert_eq!(a.used_bytes(), 29);
+    a.dealloc(
+        NonNull::new(q as *mut u8).unwrap(),
+        Layout::from_size_align(5, 1).unwrap(),
+    );
+    assert_eq!(a.used_bytes(), 24);
+    a.dealloc(p, layout);
+    assert_eq!(a.used_bytes(), 0);
+
+    let q = a.bump_bytes(5).unwrap();
+    a.dealloc(
+        NonNull::new(q as *mut u8).unwrap(),
+        Layout::from_size_align(5, 1).unwrap(),
+    );
+    assert_eq!(a.used_bytes(), 0);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "byte allocation overrun")]
+fn canary_catches_overrun_on_next_alloc() {
+    let mut arena = Arena::<1>::new(0);
+    let mut a = arena.allocator();
+    a.enable_canaries();
+    let layout = Layout::from_size_align(16, 8).unwrap();
+
+    let p = a.alloc(layout).unwrap();
+    // 越界写一个字节
+    unsafe { p.as_ptr().add(16).write(0) };
+    let _ = a.alloc(layout);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "byte allocation overrun")]
+fn canary_catches_overrun_on_dealloc() {
+    let mut arena = Arena::<1>::new(0);
+    let mut a = arena.allocator();
+    a.enable_canaries();
+    let layout = Layout::from_size_align(16, 8).unwrap();
+
+    let p = a.alloc(layout).unwrap();
+    let _q = a.alloc(layout).unwrap();
+    unsafe { p.as_ptr().add(16).write(0) };
+    a.dealloc(p, layout);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "double free: byte allocation at 0x80000010 is not outstanding")]
+fn double_free_is_caught() {
+    let mut a = new_allocator(1);
+    let layout = Layout::from_size_align(16, 8).unwrap();
+    let _p = a.alloc(layout).unwrap();
+    let q = a.alloc(layout).unwrap();
+    a.dealloc(q, layout);
+    a.dealloc(q, layout);
+}
+
+#[test]
+fn single_free_is_clean() {
+    let mut a = new_allocator(1);
+    let layout = Layout::from_size_align(16, 8).unwrap();
+    let p = a.alloc(layout).unwrap();
+    let q = a.alloc(layout).unwrap();
+    a.dealloc(p, layout);
+    a.dealloc(q, layout);
+    assert_eq!(a.used_bytes(), 0);
+
+    // 超出记录容量的分配同样可以各释放一次
+    let ptrs: [_; 100] = core::array::from_fn(|_| a.alloc(layout).unwrap());
+    for p in ptrs {
+        a.dealloc(p, layout);
+    }
+    assert_eq!(a.used_bytes(), 0);
+}
+
+#[test]
+fn stack_with_guard_page() {
+    let mut a = new_allocator(8);
+    let (stack, guard) = a.alloc_stack(2).unwrap();
+    assert_eq!(guard, 0x8000_0000 + 5 * PAGE_SIZE);
+    assert_eq!(stack, guard + PAGE_SIZE);
+    assert_eq!(a.guard_pages(), 1);
+    // 保护页与两页栈都不再可用
+    assert_eq!(a.available_pages(), 5);
+
+    // 只归还栈时保护页不会被重新分配
+    a.dealloc_pages(stack, 2);
+    assert_eq!(a.available_pages(), 5);
+    assert_eq!(a.alloc_stack(0), Err(AllocError::InvalidParam));
+}
+
+#[test]
+fn alloc_no_page_cross() {
+    let mut a = new_allocator(4);
+    let start = 0x8000_0000;
+    a.alloc(Layout::from_size_align(4000, 1).unwrap()).unwrap();
+
+    // 放在 4000 处会跨页，被推到下一页
+    let p = a.alloc_no_page_cross(200, 8).unwrap();
+    assert_eq!(p.as_ptr() as usize, start + PAGE_SIZE);
+    // 不跨页时按自然对齐分配
+    let q = a.alloc_no_page_cross(100, 8).unwrap();
+    assert_eq!(q.as_ptr() as usize, start + PAGE_SIZE + 200);
+    a.dealloc(q, Layout::from_size_align(100, 8).unwrap());
+    a.dealloc(p, Layout::from_size_align(200, 8).unwrap());
+    assert_eq!(a.used_bytes(), PAGE_SIZE);
+
+    assert_eq!(
+        a.alloc_no_page_cross(PAGE_SIZE + 1, 8),
+        Err(AllocError::InvalidParam)
+    );
+    assert_eq!(a.used_bytes(), PAGE_SIZE);
+}
+
+#[test]
+fn grow_last_in_place() {
+    let mut a = new_allocator(4);
+    let start = 0x8000_0000;
+    let p = a.alloc(Layout::from_size_align(32, 8).unwrap()).unwrap();
+    let q = a.alloc(Layout::from_size_align(16, 8).unwrap()).unwrap();
+
+    // 最顶端的分配原地扩大
+    assert_eq!(a.grow_last(q, 16, 64), Ok(()));
+    assert_eq!(a.used_bytes(), 32 + 64);
+    let r = a.alloc(Layout::from_size_align(8, 8).unwrap()).unwrap();
+    assert_eq!(r.as_ptr() as usize, start + 96);
+
+    // 不在顶端的指针、过期的旧大小都被拒绝，状态不变
+    assert_eq!(a.grow_last(p, 32, 64), Err(AllocError::InvalidParam));
+    assert_eq!(a.grow_last(q, 64, 128), Err(AllocError::InvalidParam));
+    assert_eq!(a.grow_last(r, 16, 32), Err(AllocError::InvalidParam));
+    assert_eq!(a.used_bytes(), 104);
+
+    assert_eq!(a.grow_last(r, 8, 4 * PAGE_SIZE), Err(AllocError::NoMemory));
+}
+
+#[test]
+fn export_and_import_state() {
+    let mut a = new_allocator(8);
+    let layout = Layout::from_size_align(64, 8).unwrap();
+    let p = a.alloc(layout).unwrap();
+    a.alloc_pages(2, PAGE_SIZE).unwrap();
+    let state = a.export_state();
+
+    let mut b = DefaultEarlyAllocator::new();
+    assert_eq!(b.import_state(state), Ok(()));
+    assert_eq!(b.export_state(), state);
+    assert_eq!(b.used_bytes(), 64);
+    assert_eq!(b.used_pages(), 2);
+    // 导入的分配全部释放后字节区回到起点
+    b.dealloc(p, layout);
+    assert_eq!(b.used_bytes(), 0);
+
+    let crossed = EarlyAllocatorState {
+        byte_pos: state.page_pos + 8,
+        ..state
+    };
+    assert_eq!(b.import_state(crossed), Err(AllocError::InvalidParam));
+    assert_eq!(b.used_bytes(), 0);
+}
+
+#[test]
+fn alloc_typed_objects() {
+    #[derive(Debug, PartialEq)]
+    struct Header {
+        magic: u32,
+        len: u64,
+    }
+
+    let mut arena = Arena::<1>::new(0);
+    let mut a = arena.allocator();
+    let h = unsafe {
+        a.alloc_obj_init(Header {
+            magic: 0xfeed,
+            len: 3,
+        })
+    }
+    .unwrap();
+    assert_eq!(h.as_ptr() as usize % core::mem::align_of::<Header>(), 0);
+    unsafe {
+        (*h.as_ptr()).len += 1;
+        assert_eq!(
+            *h.as_ptr(),
+            Header {
+                magic: 0xfeed,
+                len: 4
+            }
+        );
+    }
+
+    let raw = unsafe { a.alloc_obj::<u32>() }.unwrap();
+    unsafe {
+        raw.as_ptr().write(7);
+        assert_eq!(raw.as_ptr().read(), 7);
+    }
+    assert_eq!(a.used_bytes(), 16 + 4);
+
+    // 零大小类型不占用空间
+    let unit = unsafe { a.alloc_obj_init(()) }.unwrap();
+    assert_eq!(unit, NonNull::dangling());
+    assert_eq!(a.used_bytes(), 16 + 4);
+}
+
+#[test]
+fn prefault_touches_pages_in_range() {
+    let mut arena = Arena::<4>::new(0);
+    let start = arena.start();
+    let mut a = arena.allocator();
+    unsafe {
+        assert_eq!(a.prefault(start..start + 4 * PAGE_SIZE), 4);
+        // 跨越页边界的小范围触及两页
+        assert_eq!(a.prefault(start + PAGE_SIZE - 1..start + PAGE_SIZE + 1), 2);
+        // 超出区域的部分被截掉
+        assert_eq!(a.prefault(start - PAGE_SIZE..start + PAGE_SIZE), 1);
+        assert_eq!(a.prefault(start + 3 * PAGE_SIZE..usize::MAX), 1);
+        assert_eq!(a.prefault(0..start), 0);
+        assert_eq!(a.prefault(start + 2 * PAGE_SIZE..start + PAGE_SIZE), 0);
+    }
+}
+
+#[test]
+fn handoff_regions_after_mixed_allocations() {
+    let mut a = new_allocator(8);
+    let start = 0x8000_0000;
+    a.alloc(Layout::from_size_align(100, 8).unwrap()).unwrap();
+    a.alloc_pages(2, PAGE_SIZE).unwrap();
+    a.alloc(Layout::from_size_align(28, 4).unwrap()).unwrap();
+    a.alloc(Layout::from_size_align(PAGE_SIZE, PAGE_SIZE).unwrap())
+        .unwrap();
+
+    let (bytes, pages) = a.handoff_regions();
+    assert_eq!(bytes, start..start + 128);
+    assert_eq!(pages, start + 5 * PAGE_SIZE..start + 8 * PAGE_SIZE);
+    assert!(bytes.end <= pages.start);
+}
+
+#[test]
+fn alloc_best_effort_falls_back() {
+    let mut a = new_allocator(1);
+    let start = 0x8000_0000;
+    a.alloc(Layout::from_size_align(10, 1).unwrap()).unwrap();
+
+    // 按页对齐放不下，退到 64 字节对齐时恰好放下
+    let (p, align) = a.alloc_best_effort(4000, PAGE_SIZE, 8).unwrap();
+    assert_eq!(align, 64);
+    assert_eq!(p.as_ptr() as usize, start + 64);
+
+    assert_eq!(a.alloc_best_effort(64, 64, 8), Err(AllocError::NoMemory));
+    assert_eq!(a.alloc_best_effort(8, 8, 16), Err(AllocError::InvalidParam));
+    assert_eq!(a.alloc_best_effort(8, 24, 8), Err(AllocError::InvalidParam));
+}
+
+#[test]
+fn supports_layout_checks_arena_limits() {
+    let layout = |size, align| Layout::from_size_align(size, align).unwrap();
+    let mut a = DefaultEarlyAllocator::new();
+    assert!(!a.supports_layout(layout(8, 8)));
+
+    // 基址只按 1 页对齐，4 页的区域里放不下按 8 页对齐的块
+    a.init(0x8000_1000, 4 * PAGE_SIZE);
+    assert!(a.supports_layout(layout(8, PAGE_SIZE)));
+    assert!(!a.supports_layout(layout(8, 8 * PAGE_SIZE)));
+
+    // 超过整个区域的大小
+    assert!(a.supports_layout(layout(4 * PAGE_SIZE, 8)));
+    assert!(!a.supports_layout(layout(4 * PAGE_SIZE + 1, 8)));
+
+    // 暂时放不下不影响结果，永久保留的区域则会
+    a.alloc_pages(3, PAGE_SIZE).unwrap();
+    assert!(a.supports_layout(layout(2 * PAGE_SIZE, 8)));
+    a.reserve_bytes(PAGE_SIZE / 2, 8).unwrap();
+    assert!(!a.supports_layout(layout(4 * PAGE_SIZE, 8)));
+    assert!(a.supports_layout(layout(PAGE_SIZE, PAGE_SIZE)));
+}
+
+/// 反复分配再释放一次，返回字节区被整体重置的次数
+fn oscillation_resets(hysteresis: usize) -> usize {
+    let mut a = new_allocator(4);
+    a.set_reclaim_policy(ReclaimPolicy::CountReset);
+    a.set_reset_hysteresis(hysteresis);
+    let layout = Layout::from_size_align(16, 8).unwrap();
+    let mut resets = 0;
+    for _ in 0..12 {
+        let p = a.alloc(layout).unwrap();
+        a.dealloc(p, layout);
+        if a.used_bytes() == 0 {
+            resets += 1;
+        }
+    }
+    resets
+}
+
+#[test]
+fn reset_hysteresis_reduces_churn() {
+    assert_eq!(oscillation_resets(0), 12);
+    assert_eq!(oscillation_resets(4), 3);
+
+    // 未重置时空间继续向前推进
+    let mut a = new_allocator(4);
+    a.set_reclaim_policy(ReclaimPolicy::CountReset);
+    a.set_reset_hysteresis(4);
+    let layout = Layout::from_size_align(16, 8).unwrap();
+    for _ in 0..3 {
+        let p = a.alloc(layout).unwrap();
+        a.dealloc(p, layout);
+    }
+    assert_eq!(a.used_bytes(), 48);
+}
+
+#[test]
+fn reset_hysteresis_keeps_lifo_rewind() {
+    let mut a = new_allocator(4);
+    a.set_reclaim_policy(ReclaimPolicy::Lifo);
+    a.set_reset_hysteresis(4);
+    let layout = Layout::from_size_align(16, 8).unwrap();
+
+    let p = a.alloc(layout).unwrap();
+    a.dealloc(p, layout);
+    assert_eq!(a.used_bytes(), 0);
+
+    let p = a.alloc(layout).unwrap();
+    let q = a.alloc(layout).unwrap();
+    a.dealloc(q, layout);
+    assert_eq!(a.used_bytes(), 16);
+    a.dealloc(p, layout);
+    assert_eq!(a.used_bytes(), 0);
+}
+
+#[cfg(feature = "test-sync")]
+std::thread_local! {
+    /// 同步点钩子要操作的分配器，只在设置它的线程上生效
+    static HOOK_TARGET: core::cell::Cell<*const DefaultEarlyAllocator> =
+        const { core::cell::Cell::new(core::ptr::null()) };
+    /// 钩子中“另一个线程”分配到的地址
+    static HOOK_ALLOC: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+/// 在重置前的同步点上模拟另一个线程插入一次分配
+#[cfg(feature = "test-sync")]
+fn interleave_alloc(point: SyncPoint) {
+    let target = HOOK_TARGET.with(|t| t.replace(core::ptr::null()));
+    if point == SyncPoint::BeforeReset && !target.is_null() {
+        let a = unsafe { &*target };
+        let p = a
+            .alloc_bytes(Layout::from_size_align(16, 8).unwrap())
+            .unwrap();
+        HOOK_ALLOC.with(|h| h.set(p.as_ptr() as usize));
+    }
+}
+
+#[test]
+#[cfg(feature = "test-sync")]
+fn reset_cas_survives_interleaved_alloc() {
+    let layout = Layout::from_size_align(16, 8).unwrap();
+    set_sync_hook(Some(interleave_alloc));
+
+    // 旧的实现：确认计数后直接写入 byte_pos
+    let old = new_allocator(1);
+    let p = old.alloc_bytes(layout).unwrap();
+    old.byte_count.fetch_sub(1, Ordering::SeqCst);
+    HOOK_TARGET.with(|t| t.set(&old));
+    if old.byte_count.load(Ordering::SeqCst) == 0 {
+        crate::hook::sync_point(SyncPoint::BeforeReset);
+        old.byte_pos.store(old.start, Ordering::SeqCst);
+    }
+    // 插入的分配还活着，它的空间却会被再次分配出去
+    let q = HOOK_ALLOC.with(|h| h.get());
+    assert_eq!(q, p.as_ptr() as usize + 16);
+    old.alloc_bytes(layout).unwrap();
+    assert_eq!(old.alloc_bytes(layout).unwrap().as_ptr() as usize, q);
+
+    // CAS 版本：插入的分配让 CAS 失败，重置被放弃
+    let mut a = new_allocator(1);
+    let p = a.alloc(layout).unwrap();
+    HOOK_TARGET.with(|t| t.set(&a));
+    a.dealloc(p, layout);
+    let q = HOOK_ALLOC.with(|h| h.get());
+    assert_eq!(a.alloc(layout).unwrap().as_ptr() as usize, q + 16);
+    assert!(a.check_invariants());
+
+    set_sync_hook(None);
+}
@@ -0,0 +1,77 @@
+//! Boxed messages for prominent notices.
+
+use core::fmt;
+
+use super::{color_enabled, display_width, truncate_str, Colored, SliceWriter};
+use crate::ColorCode;
+
+/// Messages longer than this are cut off before being boxed.
+const BOX_BUF_LEN: usize = 512;
+
+/// Lines wider than this many columns are truncated to fit the box.
+const BOX_MAX_WIDTH: usize = 76;
+
+/// A horizontal border: `left`, `width` copies of `fill`, then `right`.
+struct Rule {
+    left: char,
+    fill: char,
+    right: char,
+    width: usize,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+
+        f.write_char(self.left)?;
+        for _ in 0..self.width {
+            f.write_char(self.fill)?;
+        }
+        f.write_char(self.right)
+    }
+}
+
+#[doc(hidden)]
+pub fn __box_println(color: ColorCode, args: fmt::Arguments) {
+    let mut buf = [0u8; BOX_BUF_LEN];
+    let mut w = SliceWriter::new(&mut buf);
+    // On overflow, box the part that fit.
+    let _ = fmt::write(&mut w, args);
+    let len = w.written();
+    let text = match core::str::from_utf8(&buf[..len]) {
+        Ok(text) => text,
+        Err(err) => core::str::from_utf8(&buf[..err.valid_up_to()]).unwrap_or_default(),
+    };
+
+    let width = text
+        .lines()
+        .map(display_width)
+        .max()
+        .unwrap_or(0)
+        .min(BOX_MAX_WIDTH);
+    let (h, v, corners) = if color_enabled() {
+        ('─', '│', ['┌', '┐', '└', '┘'])
+    } else {
+        ('-', '|', ['+', '+', '+', '+'])
+    };
+
+    let rule = |left, right| Rule {
+        left,
+        fill: h,
+        right,
+        width: width + 2,
+    };
+    crate::println!("{}", Colored::new(color, rule(corners[0], corners[1])));
+    for line in text.lines() {
+        let line = truncate_str(line, width);
+        let pad = width - display_width(line);
+        crate::println!(
+            "{}",
+            Colored::new(
+                color,
+                format_args!("{} {}{:pad$} {}", v, line, "", v, pad = pad)
+            )
+        );
+    }
+    crate::println!("{}", Colored::new(color, rule(corners[2], corners[3])));
+}
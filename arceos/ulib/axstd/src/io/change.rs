@@ -0,0 +1,86 @@
+//! Change detection for periodic status output.
+
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Values that [`println_on_change!`](crate::println_on_change) can compare,
+/// by packing them into 64 bits.
+pub trait ChangeValue: Copy {
+    /// Returns a 64-bit value that differs whenever `self` does.
+    fn change_bits(self) -> u64;
+}
+
+macro_rules! impl_change_value {
+    ($($t:ty),*) => {$(
+        impl ChangeValue for $t {
+            fn change_bits(self) -> u64 {
+                self as u64
+            }
+        }
+    )*};
+}
+
+impl_change_value!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, bool, char);
+
+impl ChangeValue for f32 {
+    fn change_bits(self) -> u64 {
+        self.to_bits() as u64
+    }
+}
+
+impl ChangeValue for f64 {
+    fn change_bits(self) -> u64 {
+        self.to_bits()
+    }
+}
+
+/// Remembers the last value seen at one call site.
+pub struct OnChange {
+    last: AtomicU64,
+    seen: AtomicBool,
+}
+
+impl OnChange {
+    /// Creates a tracker that has not seen any value yet.
+    pub const fn new() -> Self {
+        Self {
+            last: AtomicU64::new(0),
+            seen: AtomicBool::new(false),
+        }
+    }
+
+    /// Records `value`, returning whether it is the first value or differs
+    /// from the previous one.
+    pub fn update<T: ChangeValue>(&self, value: T) -> bool {
+        let bits = value.change_bits();
+        let prev = self.last.swap(bits, Ordering::Relaxed);
+        let seen = self.seen.swap(true, Ordering::Relaxed);
+        !seen || prev != bits
+    }
+}
+
+impl Default for OnChange {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Remembers whether one call site has printed already.
+pub struct Once(AtomicBool);
+
+impl Once {
+    /// Creates a flag that has not fired yet.
+    pub const fn new() -> Self {
+        Self(AtomicBool::new(false))
+    }
+
+    /// Returns `true` the first time it is called, `false` afterwards.
+    pub fn first(&self) -> bool {
+        !self.0.swap(true, Ordering::Relaxed)
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,176 @@
+//! ANSI color support for console output.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ColorCode;
+
+static COLOR_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enables or disables ANSI escape sequences in colored output.
+///
+/// When disabled, the colored printing helpers emit plain text, which is
+/// useful for consoles that do not understand escape sequences. Colors are
+/// enabled by default.
+pub fn set_color_enabled(enabled: bool) {
+    COLOR_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether ANSI escape sequences are emitted, see
+/// [`set_color_enabled`].
+pub fn color_enabled() -> bool {
+    COLOR_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Displays a value wrapped in an ANSI color, or as plain text if colors are
+/// disabled by [`set_color_enabled`].
+pub struct Colored<T> {
+    sgr: u8,
+    inner: T,
+}
+
+impl<T: fmt::Display> Colored<T> {
+    /// Wraps `inner` to be displayed in `color`.
+    pub fn new(color: ColorCode, inner: T) -> Self {
+        Self::with_sgr(color as u8, inner)
+    }
+
+    /// Wraps `inner` to be displayed in bold.
+    pub fn bold(inner: T) -> Self {
+        Self::with_sgr(1, inner)
+    }
+
+    /// Wraps `inner` to be displayed dimmed.
+    pub fn dim(inner: T) -> Self {
+        Self::with_sgr(2, inner)
+    }
+
+    /// Wraps `inner` to be displayed with the raw SGR parameter `sgr`.
+    pub(crate) fn with_sgr(sgr: u8, inner: T) -> Self {
+        Self { sgr, inner }
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Colored<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if color_enabled() {
+            write!(f, "\x1b[{}m{}\x1b[0m", self.sgr, self.inner)
+        } else {
+            self.inner.fmt(f)
+        }
+    }
+}
+
+/// Maximum number of SGR parameters a [`StyleBuilder`] can hold.
+const MAX_SGR_PARAMS: usize = 8;
+
+/// Composes several ANSI styles into a single escape sequence.
+///
+/// Parameters are emitted in the order they were added, followed by one
+/// reset at the end, e.g. `\x1b[31;44;1;4mtext\x1b[0m`. Parameters beyond
+/// the eighth are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StyleBuilder {
+    params: [u8; MAX_SGR_PARAMS],
+    len: usize,
+}
+
+impl StyleBuilder {
+    /// Creates an empty style.
+    pub const fn new() -> Self {
+        Self {
+            params: [0; MAX_SGR_PARAMS],
+            len: 0,
+        }
+    }
+
+    const fn push(mut self, param: u8) -> Self {
+        if self.len < MAX_SGR_PARAMS {
+            self.params[self.len] = param;
+            self.len += 1;
+        }
+        self
+    }
+
+    /// Sets the foreground color.
+    pub const fn fg(self, color: ColorCode) -> Self {
+        self.push(color as u8)
+    }
+
+    /// Sets the background color.
+    pub const fn bg(self, color: ColorCode) -> Self {
+        self.push(color as u8 + 10)
+    }
+
+    /// Makes the text bold.
+    pub const fn bold(self) -> Self {
+        self.push(1)
+    }
+
+    /// Underlines the text.
+    pub const fn underline(self) -> Self {
+        self.push(4)
+    }
+
+    /// Wraps `inner` to be displayed in this style, or as plain text if
+    /// colors are disabled by [`set_color_enabled`].
+    pub fn paint<T: fmt::Display>(self, inner: T) -> Styled<T> {
+        Styled { style: self, inner }
+    }
+}
+
+impl Default for StyleBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A value displayed in a [`StyleBuilder`] style, see
+/// [`StyleBuilder::paint`].
+pub struct Styled<T> {
+    style: StyleBuilder,
+    inner: T,
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = &self.style.params[..self.style.len];
+        if !color_enabled() || params.is_empty() {
+            return self.inner.fmt(f);
+        }
+        f.write_str("\x1b[")?;
+        for (i, param) in params.iter().enumerate() {
+            if i > 0 {
+                f.write_str(";")?;
+            }
+            write!(f, "{}", param)?;
+        }
+        write!(f, "m{}\x1b[0m", self.inner)
+    }
+}
+
+/// Displays `text` as an OSC 8 terminal hyperlink to `url`.
+///
+/// If escape sequences are disabled by [`set_color_enabled`], it falls back to
+/// the plain `text (url)`.
+pub struct Hyperlink<U, T> {
+    url: U,
+    text: T,
+}
+
+impl<U: fmt::Display, T: fmt::Display> Hyperlink<U, T> {
+    /// Creates a hyperlink showing `text` and pointing to `url`.
+    pub fn new(url: U, text: T) -> Self {
+        Self { url, text }
+    }
+}
+
+impl<U: fmt::Display, T: fmt::Display> fmt::Display for Hyperlink<U, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if color_enabled() {
+            write!(f, "\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", self.url, self.text)
+        } else {
+            write!(f, "{} ({})", self.text, self.url)
+        }
+    }
+}
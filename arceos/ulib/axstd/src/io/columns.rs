@@ -0,0 +1,53 @@
+//! Multi-column layout of short key/value pairs.
+
+use core::fmt;
+
+use super::width::args_width;
+use super::Colored;
+use crate::ColorCode;
+
+/// Spaces between two cells of the same row.
+const GAP: usize = 2;
+
+/// A label/value pair laid out by `columns_println!`.
+pub type Cell<'a> = (&'a dyn fmt::Display, &'a dyn fmt::Display);
+
+fn cell_width((label, value): Cell) -> usize {
+    args_width(format_args!("{}: {}", label, value))
+}
+
+/// One row of cells, each but the last padded to `width` columns.
+struct Row<'a> {
+    cells: &'a [Cell<'a>],
+    width: usize,
+}
+
+impl fmt::Display for Row<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, &(label, value)) in self.cells.iter().enumerate() {
+            write!(
+                f,
+                "{}: {}",
+                Colored::new(ColorCode::Cyan, label),
+                Colored::new(ColorCode::Yellow, value)
+            )?;
+            if i + 1 < self.cells.len() {
+                let pad = self.width - cell_width((label, value)) + GAP;
+                write!(f, "{:pad$}", "", pad = pad)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn __columns_println(cols: usize, cells: &[Cell]) {
+    let width = cells
+        .iter()
+        .map(|&cell| cell_width(cell))
+        .max()
+        .unwrap_or(0);
+    for row in cells.chunks(cols.max(1)) {
+        crate::println!("{}", Row { cells: row, width });
+    }
+}
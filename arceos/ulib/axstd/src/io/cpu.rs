@@ -0,0 +1,52 @@
+//! Boot-CPU filtering for output during SMP bring-up.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use arceos_api::config::SMP;
+
+/// Per-core flags, indexed by CPU id, set when the core's bootstrap-processor
+/// status differs from the default of `cpu_id == 0`.
+static BSP_FLIPPED: [AtomicBool; SMP] = [const { AtomicBool::new(false) }; SMP];
+
+#[cfg(not(test))]
+fn this_cpu_id() -> usize {
+    arceos_api::sys::ax_this_cpu_id()
+}
+
+#[cfg(test)]
+std::thread_local! {
+    /// The CPU id that unit tests pretend to run on.
+    pub(super) static TEST_CPU_ID: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+#[cfg(test)]
+fn this_cpu_id() -> usize {
+    TEST_CPU_ID.with(|id| id.get())
+}
+
+/// Sets whether the running core is the bootstrap processor, which decides
+/// whether [`bsp_println!`](crate::bsp_println) prints on it.
+///
+/// The flag is kept per core, so clearing it on one core does not silence
+/// the others. Core 0 is the bootstrap processor unless changed.
+pub fn set_is_bsp(is_bsp: bool) {
+    let id = this_cpu_id();
+    if let Some(flipped) = BSP_FLIPPED.get(id) {
+        flipped.store(is_bsp != (id == 0), Ordering::Relaxed);
+    }
+}
+
+/// Marks the running core as the bootstrap processor if `cpu_id` is 0.
+///
+/// Equivalent to `set_is_bsp(cpu_id == 0)`.
+pub fn set_current_cpu(cpu_id: usize) {
+    set_is_bsp(cpu_id == 0);
+}
+
+/// Returns whether the running core is marked as the bootstrap processor.
+pub fn is_bsp() -> bool {
+    let id = this_cpu_id();
+    BSP_FLIPPED
+        .get(id)
+        .is_some_and(|flipped| flipped.load(Ordering::Relaxed) != (id == 0))
+}
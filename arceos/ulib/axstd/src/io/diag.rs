@@ -0,0 +1,94 @@
+//! Non-fatal diagnostics for bring-up code.
+
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::Colored;
+use crate::ColorCode;
+
+static SOFT_ASSERT_FAILURES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns how many [`soft_assert!`] checks have failed so far.
+///
+/// [`soft_assert!`]: crate::soft_assert
+pub fn soft_assert_failures() -> usize {
+    SOFT_ASSERT_FAILURES.load(Ordering::Relaxed)
+}
+
+#[doc(hidden)]
+pub fn __soft_assert_failed(cond: &str, file: &str, line: u32, msg: Option<fmt::Arguments>) {
+    SOFT_ASSERT_FAILURES.fetch_add(1, Ordering::Relaxed);
+    match msg {
+        Some(msg) => crate::color_println!(
+            ColorCode::Red,
+            "[WARN] {}:{}: soft assertion failed: {}: {}",
+            file,
+            line,
+            cond,
+            msg
+        ),
+        None => crate::color_println!(
+            ColorCode::Red,
+            "[WARN] {}:{}: soft assertion failed: {}",
+            file,
+            line,
+            cond
+        ),
+    }
+}
+
+/// Prints one backtrace frame as `#idx  0xADDR  symbol`.
+///
+/// The index is dimmed and the address is shown in cyan, unless colors are
+/// disabled by [`set_color_enabled`]. Frames without a resolved symbol are
+/// shown as `<unknown>`. Collecting the frames is up to the caller, e.g. an
+/// unwinder walking the stack from the panic handler.
+///
+/// [`set_color_enabled`]: super::set_color_enabled
+pub fn print_backtrace_frame(idx: usize, pc: usize, sym: Option<&str>) {
+    crate::println!(
+        "{}  {}  {}",
+        Colored::dim(format_args!("#{:<2}", idx)),
+        Colored::new(
+            ColorCode::Cyan,
+            format_args!("{:#0w$x}", pc, w = 2 + 2 * core::mem::size_of::<usize>())
+        ),
+        sym.unwrap_or("<unknown>")
+    );
+}
+
+#[doc(hidden)]
+pub fn __print_field_diff(name: &str, values: fmt::Arguments, delta: isize, grows_down: bool) {
+    // Positive when the change means more memory in use.
+    let usage = if grows_down { -delta } else { delta };
+    let color = match usage.cmp(&0) {
+        core::cmp::Ordering::Greater => Some(ColorCode::Red),
+        core::cmp::Ordering::Less => Some(ColorCode::Green),
+        core::cmp::Ordering::Equal => None,
+    };
+    match color {
+        Some(color) => {
+            crate::println!(
+                "{:<10} {}  {}",
+                name,
+                values,
+                Colored::new(color, format_args!("{:+}", delta))
+            );
+        }
+        None => {
+            crate::println!("{:<10} {}  {:+}", name, values, delta);
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn __error_chain(err: &dyn core::error::Error) {
+    crate::color_println!(ColorCode::Red, "{}", err);
+    let mut source = err.source();
+    let mut depth = 1;
+    while let Some(err) = source {
+        crate::println!("{:indent$}caused by: {}", "", err, indent = 2 * depth);
+        source = err.source();
+        depth += 1;
+    }
+}
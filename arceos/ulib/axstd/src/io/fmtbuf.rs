@@ -0,0 +1,52 @@
+//! Formatting into caller-provided byte buffers.
+
+use core::fmt;
+
+use super::Colored;
+use crate::ColorCode;
+
+/// A [`fmt::Write`] sink that fills a byte slice.
+///
+/// Writing more than fits stores as much of the string as there is room for
+/// and then fails with [`fmt::Error`].
+pub struct SliceWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceWriter<'a> {
+    /// Creates a writer that fills `buf` from the beginning.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, len: 0 }
+    }
+
+    /// Returns how many bytes have been written so far.
+    pub fn written(&self) -> usize {
+        self.len
+    }
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let room = self.buf.len() - self.len;
+        let n = s.len().min(room);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        if n < s.len() {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn __color_format(
+    buf: &mut [u8],
+    color: ColorCode,
+    args: fmt::Arguments,
+) -> Result<usize, fmt::Error> {
+    let mut w = SliceWriter::new(buf);
+    fmt::write(&mut w, format_args!("{}", Colored::new(color, args)))?;
+    Ok(w.written())
+}
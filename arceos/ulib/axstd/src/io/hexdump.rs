@@ -0,0 +1,189 @@
+//! Hexadecimal dumps of byte buffers.
+
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use super::Colored;
+use crate::ColorCode;
+
+/// Number of bytes shown on each row of a dump.
+const BYTES_PER_ROW: usize = 16;
+
+/// Displays a byte buffer as rows of offset, hex bytes and ASCII.
+///
+/// ```text
+/// 00000000  48 65 6c 6c 6f 2c 20 41 72 63 65 4f 53 21 0a 00  |Hello, ArceOS!..|
+/// ```
+pub struct HexDump<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> HexDump<'a> {
+    /// Creates a new [`HexDump`] of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (row, chunk) in self.bytes.chunks(BYTES_PER_ROW).enumerate() {
+            write!(f, "{:08x} ", row * BYTES_PER_ROW)?;
+            write!(f, "{}", HexRow(chunk))?;
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// The hex bytes and ASCII column of one row of a [`HexDump`].
+struct HexRow<'a>(&'a [u8]);
+
+impl fmt::Display for HexRow<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for col in 0..BYTES_PER_ROW {
+            match self.0.get(col) {
+                Some(b) => write!(f, " {:02x}", b)?,
+                None => f.write_str("   ")?,
+            }
+        }
+        f.write_str("  |")?;
+        for &b in self.0 {
+            let c = if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            };
+            write!(f, "{}", c)?;
+        }
+        f.write_str("|")
+    }
+}
+
+/// Displays two byte buffers side by side as hex, highlighting the bytes
+/// that differ in red.
+///
+/// If the buffers have different lengths, the excess bytes of the longer one
+/// are highlighted as differences and the missing bytes of the shorter one
+/// are shown as `--`.
+pub struct BufferDiff<'a> {
+    a: &'a [u8],
+    b: &'a [u8],
+}
+
+impl<'a> BufferDiff<'a> {
+    /// Creates a new [`BufferDiff`] comparing `a` against `b`.
+    pub fn new(a: &'a [u8], b: &'a [u8]) -> Self {
+        Self { a, b }
+    }
+
+    fn write_row(
+        f: &mut fmt::Formatter<'_>,
+        this: &[u8],
+        other: &[u8],
+        offset: usize,
+    ) -> fmt::Result {
+        for i in offset..offset + BYTES_PER_ROW {
+            match (this.get(i), other.get(i)) {
+                (Some(x), Some(y)) if x == y => write!(f, " {:02x}", x)?,
+                (Some(x), _) => write!(
+                    f,
+                    " {}",
+                    Colored::new(ColorCode::Red, format_args!("{:02x}", x))
+                )?,
+                (None, Some(_)) => f.write_str(" --")?,
+                (None, None) => f.write_str("   ")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BufferDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let len = self.a.len().max(self.b.len());
+        for offset in (0..len).step_by(BYTES_PER_ROW) {
+            write!(f, "{:08x} ", offset)?;
+            Self::write_row(f, self.a, self.b, offset)?;
+            f.write_str("  |")?;
+            Self::write_row(f, self.b, self.a, offset)?;
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints a hex dump of `bytes` to the standard output.
+pub fn hexdump(bytes: &[u8]) {
+    crate::print!("{}", HexDump::new(bytes));
+}
+
+/// Prints `a` and `b` side by side as hex with differing bytes highlighted,
+/// see [`BufferDiff`].
+pub fn print_buffer_diff(a: &[u8], b: &[u8]) {
+    crate::print!("{}", BufferDiff::new(a, b));
+}
+
+/// How [`dump_region`] waits between pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DumpPause {
+    /// Print a `-- more --` prompt and wait for a key on the standard input.
+    Key,
+    /// Sleep for the given time, without a prompt. A zero delay does not
+    /// pause at all.
+    Delay(Duration),
+}
+
+/// The pause between pages in milliseconds, or `u64::MAX` to wait for a key.
+static DUMP_PAUSE_MS: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Sets how [`dump_region`] waits between pages. Waits for a key by default.
+pub fn set_dump_pause(pause: DumpPause) {
+    let ms = match pause {
+        DumpPause::Key => u64::MAX,
+        DumpPause::Delay(delay) => (delay.as_millis() as u64).min(u64::MAX - 1),
+    };
+    DUMP_PAUSE_MS.store(ms, Ordering::Relaxed);
+}
+
+fn pause_between_pages() {
+    match DUMP_PAUSE_MS.load(Ordering::Relaxed) {
+        u64::MAX => {
+            crate::println!("{}", Colored::dim("-- more --"));
+            super::stdio::wait_key();
+        }
+        0 => {}
+        ms => crate::thread::sleep(Duration::from_millis(ms)),
+    }
+}
+
+/// Prints a hex dump of `bytes` in pages of `page_rows` rows, pausing between
+/// pages as set by [`set_dump_pause`] so that output does not scroll off a
+/// slow console.
+///
+/// Offsets are cyan, and rows of all zero bytes are dimmed, unless colors are
+/// disabled by [`set_color_enabled`](super::set_color_enabled). A region
+/// that fits in one page is printed without pausing. A `page_rows` of zero
+/// is treated as one.
+pub fn dump_region(bytes: &[u8], page_rows: usize) {
+    let page_rows = page_rows.max(1);
+    for (row, chunk) in bytes.chunks(BYTES_PER_ROW).enumerate() {
+        if row > 0 && row % page_rows == 0 {
+            pause_between_pages();
+        }
+        let offset = row * BYTES_PER_ROW;
+        if chunk.iter().all(|&b| b == 0) {
+            crate::println!(
+                "{}",
+                Colored::dim(format_args!("{:08x} {}", offset, HexRow(chunk)))
+            );
+        } else {
+            crate::println!(
+                "{} {}",
+                Colored::new(ColorCode::Cyan, format_args!("{:08x}", offset)),
+                HexRow(chunk)
+            );
+        }
+    }
+}
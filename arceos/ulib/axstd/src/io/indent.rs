@@ -0,0 +1,51 @@
+//! Indentation for nested diagnostic output.
+
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::sync::Mutex;
+
+static INDENT_LEVEL: AtomicUsize = AtomicUsize::new(0);
+static INDENT_UNIT: Mutex<&'static str> = Mutex::new("  ");
+
+/// Increases the indentation of [`iprintln!`](crate::iprintln) by one unit.
+pub fn indent() {
+    INDENT_LEVEL.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Decreases the indentation of [`iprintln!`](crate::iprintln) by one unit.
+/// Does nothing at level zero.
+pub fn dedent() {
+    let _ = INDENT_LEVEL.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |level| {
+        level.checked_sub(1)
+    });
+}
+
+/// Returns the current indentation level, in units.
+pub fn indent_level() -> usize {
+    INDENT_LEVEL.load(Ordering::Relaxed)
+}
+
+/// Sets the string printed once per indentation level. Defaults to two
+/// spaces.
+pub fn set_indent_unit(unit: &'static str) {
+    *INDENT_UNIT.lock() = unit;
+}
+
+/// `level` copies of `unit`.
+struct Indent(&'static str, usize);
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for _ in 0..self.1 {
+            f.write_str(self.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn __iprintln(args: fmt::Arguments) {
+    let unit = *INDENT_UNIT.lock();
+    crate::println!("{}{}", Indent(unit, indent_level()), args);
+}
@@ -0,0 +1,109 @@
+//! Minimal JSON output for exporting data to host tools.
+
+use core::fmt;
+
+/// Values that [`json_println!`](crate::json_println) can print: numbers
+/// and booleans are written bare, strings and characters quoted and escaped.
+pub trait JsonValue {
+    /// Writes `self` as a JSON value.
+    fn fmt_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result;
+}
+
+macro_rules! impl_json_bare {
+    ($($t:ty),*) => {$(
+        impl JsonValue for $t {
+            fn fmt_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "{}", self)
+            }
+        }
+    )*};
+}
+
+impl_json_bare!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, bool);
+
+macro_rules! impl_json_float {
+    ($($t:ty),*) => {$(
+        /// NaN and infinities have no JSON form and are written as `null`.
+        impl JsonValue for $t {
+            fn fmt_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                if self.is_finite() {
+                    write!(f, "{}", self)
+                } else {
+                    f.write_str("null")
+                }
+            }
+        }
+    )*};
+}
+
+impl_json_float!(f32, f64);
+
+impl JsonValue for str {
+    fn fmt_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", JsonStr(&self))
+    }
+}
+
+impl JsonValue for char {
+    fn fmt_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", JsonStr(self))
+    }
+}
+
+impl<T: JsonValue + ?Sized> JsonValue for &T {
+    fn fmt_json(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt_json(f)
+    }
+}
+
+/// Displays a value as a quoted JSON string, escaping quotes, backslashes
+/// and control characters.
+struct JsonStr<'a>(&'a dyn fmt::Display);
+
+impl fmt::Display for JsonStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Escape<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+        impl fmt::Write for Escape<'_, '_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                for c in s.chars() {
+                    match c {
+                        '"' => self.0.write_str("\\\"")?,
+                        '\\' => self.0.write_str("\\\\")?,
+                        '\n' => self.0.write_str("\\n")?,
+                        '\r' => self.0.write_str("\\r")?,
+                        '\t' => self.0.write_str("\\t")?,
+                        c if c.is_control() => write!(self.0, "\\u{:04x}", c as u32)?,
+                        c => fmt::Write::write_char(self.0, c)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        f.write_str("\"")?;
+        fmt::write(&mut Escape(f), format_args!("{}", self.0))?;
+        f.write_str("\"")
+    }
+}
+
+#[doc(hidden)]
+pub fn __json_println(fields: &[(&dyn fmt::Display, &dyn JsonValue)]) {
+    struct Object<'a>(&'a [(&'a dyn fmt::Display, &'a dyn JsonValue)]);
+
+    impl fmt::Display for Object<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("{")?;
+            for (i, &(key, value)) in self.0.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(",")?;
+                }
+                write!(f, "{}:", JsonStr(key))?;
+                value.fmt_json(f)?;
+            }
+            f.write_str("}")
+        }
+    }
+
+    crate::println!("{}", Object(fields));
+}
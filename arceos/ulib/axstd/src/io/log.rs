@@ -0,0 +1,190 @@
+//! Verbosity levels for the logging macros.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use super::{color_enabled, Colored};
+use crate::ColorCode;
+
+/// The verbosity of a log message, from the most to the least severe.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    /// Printed by [`error!`](crate::error).
+    Error = 1,
+    /// Printed by [`warn!`](crate::warn).
+    Warn,
+    /// Printed by [`info!`](crate::info).
+    Info,
+    /// Printed by [`debug!`](crate::debug).
+    Debug,
+    /// Printed by [`trace!`](crate::trace).
+    Trace,
+}
+
+impl LogLevel {
+    /// Returns the lowercase name of the level, e.g. `"info"`.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+
+    /// Returns the tag printed by the log macros, e.g. `"INFO"`.
+    pub const fn tag(self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+
+    /// Returns the color the log macros print this level in.
+    pub const fn color(self) -> ColorCode {
+        match self {
+            Self::Error => ColorCode::Red,
+            Self::Warn => ColorCode::Yellow,
+            Self::Info => ColorCode::Green,
+            Self::Debug => ColorCode::Cyan,
+            Self::Trace => ColorCode::BrightBlack,
+        }
+    }
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Trace as u8);
+static BELL_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// Sets the most verbose level that the logging macros still print.
+///
+/// Messages above `level` are skipped before they are formatted. All levels
+/// are printed by default.
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Returns the level set by [`set_log_level`].
+pub fn log_level() -> LogLevel {
+    match LOG_LEVEL.load(Ordering::Relaxed) {
+        1 => LogLevel::Error,
+        2 => LogLevel::Warn,
+        3 => LogLevel::Info,
+        4 => LogLevel::Debug,
+        _ => LogLevel::Trace,
+    }
+}
+
+/// Makes [`error!`](crate::error) ring the terminal bell (`BEL`, `\x07`)
+/// before each message, to catch failures during unattended boots.
+///
+/// Like other control sequences, the bell is not emitted while escape
+/// sequences are disabled by [`set_color_enabled`]. Off by default.
+///
+/// [`set_color_enabled`]: super::set_color_enabled
+pub fn set_bell_on_error(enabled: bool) {
+    BELL_ON_ERROR.store(enabled, Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+pub fn __log_prefix(level: LogLevel) -> &'static str {
+    if level == LogLevel::Error && BELL_ON_ERROR.load(Ordering::Relaxed) && color_enabled() {
+        "\x07"
+    } else {
+        ""
+    }
+}
+
+#[doc(hidden)]
+#[inline]
+pub fn __log_enabled(level: LogLevel) -> bool {
+    level as u8 <= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+/// A value formatted for logfmt: quoted if empty or if it contains a space,
+/// `=` or `"`, with `"` and `\` escaped inside the quotes.
+struct LogfmtValue<'a>(&'a dyn fmt::Display);
+
+impl fmt::Display for LogfmtValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct Scan {
+            empty: bool,
+            quote: bool,
+        }
+
+        impl fmt::Write for Scan {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.empty &= s.is_empty();
+                self.quote |= s.contains([' ', '=', '"']);
+                Ok(())
+            }
+        }
+
+        struct Escape<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+        impl fmt::Write for Escape<'_, '_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                for c in s.chars() {
+                    if c == '"' || c == '\\' {
+                        self.0.write_str("\\")?;
+                    }
+                    fmt::Write::write_char(self.0, c)?;
+                }
+                Ok(())
+            }
+        }
+
+        let mut scan = Scan {
+            empty: true,
+            quote: false,
+        };
+        let _ = fmt::write(&mut scan, format_args!("{}", self.0));
+        if scan.empty || scan.quote {
+            f.write_str("\"")?;
+            fmt::write(&mut Escape(f), format_args!("{}", self.0))?;
+            f.write_str("\"")
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn __logfmt(level: LogLevel, fields: &[(&dyn fmt::Display, &dyn fmt::Display)]) {
+    struct Line<'a>(LogLevel, &'a [(&'a dyn fmt::Display, &'a dyn fmt::Display)]);
+
+    impl fmt::Display for Line<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "level={}", self.0.as_str())?;
+            for &(key, value) in self.1 {
+                write!(f, " {}={}", key, LogfmtValue(value))?;
+            }
+            Ok(())
+        }
+    }
+
+    if __log_enabled(level) {
+        crate::println!("{}", Line(level, fields));
+    }
+}
+
+#[doc(hidden)]
+pub fn __log_err(level: LogLevel, args: fmt::Arguments) {
+    if !__log_enabled(level) {
+        return;
+    }
+    let sink = if level <= LogLevel::Warn {
+        super::stdio::__eprint_impl
+    } else {
+        super::stdio::__print_impl
+    };
+    sink(format_args!(
+        "{}{}\n",
+        __log_prefix(level),
+        Colored::new(level.color(), format_args!("[{}] {}", level.tag(), args))
+    ));
+}
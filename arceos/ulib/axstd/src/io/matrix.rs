@@ -0,0 +1,84 @@
+//! Grid output for small matrices and tables of numbers.
+
+use core::fmt;
+
+use super::width::args_width;
+use super::Colored;
+use crate::ColorCode;
+
+/// One line of a matrix: the header when `row` is `None`.
+struct Line<'a, T, R> {
+    rows: &'a [R],
+    row: Option<usize>,
+    cols: usize,
+    label_width: usize,
+    cell_width: usize,
+    highlight: Option<ColorCode>,
+    _marker: core::marker::PhantomData<T>,
+}
+
+impl<T, R> fmt::Display for Line<'_, T, R>
+where
+    T: fmt::Display + Default + PartialEq,
+    R: AsRef<[T]>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(row) = self.row else {
+            write!(f, "{:w$}", "", w = self.label_width)?;
+            for col in 0..self.cols {
+                let pad = self.cell_width - args_width(format_args!("{}", col));
+                write!(f, " {:pad$}{}", "", Colored::dim(col), pad = pad)?;
+            }
+            return Ok(());
+        };
+
+        let pad = self.label_width - args_width(format_args!("{}", row));
+        write!(f, "{:pad$}{}", "", Colored::dim(row), pad = pad)?;
+        let cells = self.rows[row].as_ref();
+        for col in 0..self.cols {
+            let Some(cell) = cells.get(col) else {
+                // Ragged rows are padded with blanks.
+                write!(f, " {:w$}", "", w = self.cell_width)?;
+                continue;
+            };
+            let pad = self.cell_width - args_width(format_args!("{}", cell));
+            write!(f, " {:pad$}", "", pad = pad)?;
+            match self.highlight {
+                Some(color) if *cell != T::default() => write!(f, "{}", Colored::new(color, cell))?,
+                _ => write!(f, "{}", cell)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn __matrix_println<T, R>(rows: &[R], highlight: Option<ColorCode>)
+where
+    T: fmt::Display + Default + PartialEq,
+    R: AsRef<[T]>,
+{
+    let cols = rows.iter().map(|r| r.as_ref().len()).max().unwrap_or(0);
+    let label_width = args_width(format_args!("{}", rows.len().saturating_sub(1)));
+    let cell_width = rows
+        .iter()
+        .flat_map(|r| r.as_ref())
+        .map(|cell| args_width(format_args!("{}", cell)))
+        .chain([args_width(format_args!("{}", cols.saturating_sub(1)))])
+        .max()
+        .unwrap_or(0);
+    let mut line = Line {
+        rows,
+        row: None,
+        cols,
+        label_width,
+        cell_width,
+        highlight,
+        _marker: core::marker::PhantomData,
+    };
+    crate::println!("{}", line);
+    for row in 0..rows.len() {
+        line.row = Some(row);
+        crate::println!("{}", line);
+    }
+}
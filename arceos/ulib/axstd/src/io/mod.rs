@@ -1,13 +1,74 @@
 //! Traits, helpers, and type definitions for core I/O functionality.
 
+mod boxed;
+mod change;
+mod color;
+mod columns;
+mod cpu;
+mod diag;
+mod fmtbuf;
+mod hexdump;
+mod indent;
+mod json;
+mod log;
+mod matrix;
+mod numfmt;
+mod progress;
+mod ring;
 mod stdio;
+#[cfg(test)]
+mod tests;
+mod tree;
+mod variant;
+mod width;
 
 pub use axio::prelude;
 pub use axio::{BufRead, BufReader, Error, Read, Seek, SeekFrom, Write};
 
 #[doc(hidden)]
-pub use self::stdio::__print_impl;
+pub use self::boxed::__box_println;
+pub use self::change::{ChangeValue, OnChange, Once};
+pub use self::color::{color_enabled, set_color_enabled, Colored, Hyperlink, StyleBuilder, Styled};
+#[doc(hidden)]
+pub use self::columns::__columns_println;
+pub use self::cpu::{is_bsp, set_current_cpu, set_is_bsp};
+#[doc(hidden)]
+pub use self::diag::{__error_chain, __print_field_diff, __soft_assert_failed};
+pub use self::diag::{print_backtrace_frame, soft_assert_failures};
+pub use self::fmtbuf::SliceWriter;
+#[doc(hidden)]
+pub use self::fmtbuf::__color_format;
+pub use self::hexdump::{dump_region, hexdump, print_buffer_diff, BufferDiff, HexDump};
+pub use self::hexdump::{set_dump_pause, DumpPause};
+#[doc(hidden)]
+pub use self::indent::__iprintln;
+pub use self::indent::{dedent, indent, indent_level, set_indent_unit};
+pub use self::json::JsonValue;
+#[doc(hidden)]
+pub use self::json::__json_println;
+#[doc(hidden)]
+pub use self::log::{__log_enabled, __log_err, __log_prefix, __logfmt};
+pub use self::log::{log_level, set_bell_on_error, set_log_level, LogLevel};
+#[doc(hidden)]
+pub use self::matrix::__matrix_println;
+#[doc(hidden)]
+pub use self::numfmt::__regs_println;
+pub use self::numfmt::{Bits, ByteSize, GroupedBinary, MacAddr, PaddedHex, Scaled};
+pub use self::progress::{spinner_frame, Gauge, StepMarker};
+#[doc(hidden)]
+pub use self::ring::__dump_log_ring;
+pub use self::ring::{clear_log_ring, log_capture, set_log_capture, LogRing};
+pub use self::ring::{LOG_RING_LINES, LOG_RING_WIDTH};
+#[doc(hidden)]
+pub use self::stdio::{__eprint_impl, __print_impl};
+pub use self::stdio::{end_print_window, set_flush_per_line, set_print_budget, try_print};
 pub use self::stdio::{stdin, stdout, Stdin, StdinLock, Stdout, StdoutLock};
+pub use self::tree::{print_tree, Tree, TreeNode};
+pub use self::variant::VariantName;
+#[doc(hidden)]
+pub use self::width::{__center_println, __rule_println, __wrap_println};
+pub use self::width::{display_width, truncate_str, truncate_to_width};
+pub use self::width::{set_terminal_width, terminal_width};
 
 /// A specialized [`Result`] type for I/O operations.
 ///
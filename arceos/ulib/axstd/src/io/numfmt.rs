@@ -0,0 +1,225 @@
+//! Helpers for printing numbers in debugging-friendly forms.
+
+use core::fmt;
+
+use super::Colored;
+use crate::ColorCode;
+
+/// Unsigned integer types whose bits can be printed one by one.
+pub trait Bits: Copy {
+    /// The width of the type in bits.
+    const BITS: u32;
+
+    /// Returns whether bit `n` (counted from the least significant bit) is set.
+    fn bit(self, n: u32) -> bool;
+}
+
+macro_rules! impl_bits {
+    ($($t:ty),*) => {$(
+        impl Bits for $t {
+            const BITS: u32 = <$t>::BITS;
+
+            fn bit(self, n: u32) -> bool {
+                (self >> n) & 1 != 0
+            }
+        }
+    )*};
+}
+
+impl_bits!(u8, u16, u32, u64, usize);
+
+/// Displays an integer in binary, most significant bit first, with leading
+/// zeros up to the type's width and a `_` between every `group` bits.
+///
+/// Groups are counted from the least significant bit. A `group` of zero
+/// disables the separators.
+pub struct GroupedBinary<T> {
+    value: T,
+    group: usize,
+}
+
+impl<T: Bits> GroupedBinary<T> {
+    /// Creates a new [`GroupedBinary`] for `value`.
+    pub fn new(value: T, group: usize) -> Self {
+        Self { value, group }
+    }
+}
+
+impl<T: Bits> fmt::Display for GroupedBinary<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for n in (0..T::BITS).rev() {
+            if self.group > 0 && n + 1 < T::BITS && (n as usize + 1) % self.group == 0 {
+                f.write_str("_")?;
+            }
+            f.write_str(if self.value.bit(n) { "1" } else { "0" })?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays an integer as `0x` followed by hex digits zero-padded to the
+/// width of its type, e.g. 8 digits for `u32` and 16 for `u64`.
+///
+/// Signed values are shown in two's complement. The `0x` prefix is dimmed and
+/// the digits are colored (cyan by default), unless colors are disabled by
+/// [`set_color_enabled`].
+///
+/// [`set_color_enabled`]: super::set_color_enabled
+pub struct PaddedHex<T> {
+    value: T,
+    color: ColorCode,
+}
+
+impl<T: fmt::LowerHex> PaddedHex<T> {
+    /// Creates a new [`PaddedHex`] for `value` with cyan digits.
+    pub fn new(value: T) -> Self {
+        Self::with_color(value, ColorCode::Cyan)
+    }
+
+    /// Creates a new [`PaddedHex`] for `value` with digits in `color`.
+    pub fn with_color(value: T, color: ColorCode) -> Self {
+        Self { value, color }
+    }
+}
+
+impl<T: fmt::LowerHex> fmt::Display for PaddedHex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = 2 * core::mem::size_of::<T>();
+        write!(
+            f,
+            "{}{}",
+            Colored::dim("0x"),
+            Colored::new(self.color, format_args!("{:0w$x}", self.value, w = width))
+        )
+    }
+}
+
+/// Displays a MAC address as six colon-separated lowercase hex bytes, e.g.
+/// `52:54:00:12:34:56`.
+pub struct MacAddr(pub [u8; 6]);
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, b) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Scales `value` by powers of `step` to the largest of `units` units that
+/// keeps the integer part at or above 1, rounded to `precision` decimals.
+///
+/// Returns the unit index and the rounded value in units of
+/// `10^-precision`. If rounding reaches a full step (e.g. `1024.00`), the next
+/// unit is used instead. Unit 0 is never rounded.
+fn scale(value: u64, step: u128, units: usize, precision: u32) -> (usize, u128) {
+    let value = value as u128;
+    let one = 10u128.pow(precision);
+    let mut unit = 0;
+    let mut divisor = 1;
+    while unit + 1 < units && value >= divisor * step {
+        unit += 1;
+        divisor *= step;
+    }
+    if unit == 0 {
+        return (0, value * one);
+    }
+    let mut scaled = (value * one + divisor / 2) / divisor;
+    if scaled >= step * one && unit + 1 < units {
+        unit += 1;
+        divisor *= step;
+        scaled = (value * one + divisor / 2) / divisor;
+    }
+    (unit, scaled)
+}
+
+/// Displays a byte count with binary units (`B`, `KiB`, `MiB`, `GiB`).
+///
+/// Values below 1 KiB are printed as whole bytes; larger ones are rounded to
+/// two decimals in the largest unit that keeps the integer part at or above 1,
+/// e.g. `1.00 KiB` for 1024.
+pub struct ByteSize(pub u64);
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+        match scale(self.0, 1024, UNITS.len(), 2) {
+            (0, _) => write!(f, "{} B", self.0),
+            (unit, hundredths) => write!(
+                f,
+                "{}.{:02} {}",
+                hundredths / 100,
+                hundredths % 100,
+                UNITS[unit]
+            ),
+        }
+    }
+}
+
+/// Displays a count with an automatically chosen scale prefix and one
+/// decimal, e.g. `1.5 KiB` for 1536 bytes or `2.3 kHz` for 2300 hertz.
+///
+/// A unit of `B` is scaled by powers of 1024 with binary prefixes (`Ki`,
+/// `Mi`, ...), any other unit by powers of 1000 with SI prefixes (`k`, `M`,
+/// ...). Counts below the first step are printed unscaled. The number is cyan
+/// and the unit dimmed, unless colors are disabled by [`set_color_enabled`].
+///
+/// [`set_color_enabled`]: super::set_color_enabled
+pub struct Scaled<'a> {
+    value: u64,
+    unit: &'a str,
+}
+
+impl<'a> Scaled<'a> {
+    /// Creates a new [`Scaled`] for `value` counted in `unit`.
+    pub fn new(value: u64, unit: &'a str) -> Self {
+        Self { value, unit }
+    }
+}
+
+impl fmt::Display for Scaled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (step, prefixes): (u128, [&str; 7]) = if self.unit == "B" {
+            (1024, ["", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei"])
+        } else {
+            (1000, ["", "k", "M", "G", "T", "P", "E"])
+        };
+
+        match scale(self.value, step, prefixes.len(), 1) {
+            (0, _) => write!(
+                f,
+                "{} {}",
+                Colored::new(ColorCode::Cyan, self.value),
+                Colored::dim(self.unit)
+            ),
+            (scale, tenths) => write!(
+                f,
+                "{} {}",
+                Colored::new(
+                    ColorCode::Cyan,
+                    format_args!("{}.{}", tenths / 10, tenths % 10)
+                ),
+                Colored::dim(format_args!("{}{}", prefixes[scale], self.unit))
+            ),
+        }
+    }
+}
+
+#[doc(hidden)]
+pub fn __regs_println(regs: &[(&str, u64)]) {
+    let width = regs.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for &(name, value) in regs {
+        crate::println!(
+            "{:<width$}  {:#018x}  {:>20}",
+            name,
+            value,
+            value,
+            width = width
+        );
+    }
+}
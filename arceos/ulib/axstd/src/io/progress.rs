@@ -0,0 +1,116 @@
+//! Progress indicators for long-running boot stages.
+
+use core::fmt;
+
+use super::{color_enabled, Colored};
+use crate::ColorCode;
+
+/// Returns the number of decimal digits of `n`.
+fn decimal_width(n: usize) -> usize {
+    n.checked_ilog10().unwrap_or(0) as usize + 1
+}
+
+/// Returns the spinner frame for `state`, cycling through `|`, `/`, `-` and
+/// `\`.
+pub fn spinner_frame(state: usize) -> char {
+    const FRAMES: [char; 4] = ['|', '/', '-', '\\'];
+    FRAMES[state % FRAMES.len()]
+}
+
+/// Displays a `[n/total]` step marker, with `n` right-aligned to the width
+/// of `total`.
+///
+/// The marker is yellow during the first half of the steps, bright green
+/// during the second half and green on the last step.
+pub struct StepMarker {
+    n: usize,
+    total: usize,
+}
+
+impl StepMarker {
+    /// Creates the marker for step `n` out of `total`.
+    pub fn new(n: usize, total: usize) -> Self {
+        Self { n, total }
+    }
+
+    /// Returns the color of the marker.
+    pub fn color(&self) -> ColorCode {
+        if self.n >= self.total {
+            ColorCode::Green
+        } else if self.n * 2 >= self.total {
+            ColorCode::BrightGreen
+        } else {
+            ColorCode::Yellow
+        }
+    }
+}
+
+impl fmt::Display for StepMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = decimal_width(self.total);
+        write!(
+            f,
+            "{}",
+            Colored::new(
+                self.color(),
+                format_args!("[{:>width$}/{}]", self.n, self.total, width = width)
+            )
+        )
+    }
+}
+
+/// Number of cells in a [`Gauge`] bar.
+const GAUGE_CELLS: u32 = 10;
+
+/// Displays a permille value as an inline bar followed by the percentage,
+/// e.g. `[██████····] 62.3%`.
+///
+/// The filled cells are green below 70%, yellow below 90% and red from 90%
+/// on. Values above 1000‰ are clamped. When escape sequences are disabled by
+/// [`set_color_enabled`], only the percentage is shown.
+///
+/// [`set_color_enabled`]: super::set_color_enabled
+pub struct Gauge {
+    permille: u32,
+}
+
+impl Gauge {
+    /// Creates a gauge for `permille` parts per thousand.
+    pub fn new(permille: u32) -> Self {
+        Self {
+            permille: permille.min(1000),
+        }
+    }
+
+    /// Returns the color of the filled cells.
+    pub fn color(&self) -> ColorCode {
+        if self.permille >= 900 {
+            ColorCode::Red
+        } else if self.permille >= 700 {
+            ColorCode::Yellow
+        } else {
+            ColorCode::Green
+        }
+    }
+}
+
+impl fmt::Display for Gauge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use fmt::Write;
+
+        if color_enabled() {
+            let filled = self.permille * GAUGE_CELLS / 1000;
+            f.write_str("[")?;
+            write!(f, "\x1b[{}m", self.color() as u8)?;
+            for _ in 0..filled {
+                f.write_char('█')?;
+            }
+            f.write_str("\x1b[0m")?;
+            for _ in filled..GAUGE_CELLS {
+                f.write_char('·')?;
+            }
+            f.write_str("] ")?;
+        }
+        write!(f, "{}.{}%", self.permille / 10, self.permille % 10)
+    }
+}
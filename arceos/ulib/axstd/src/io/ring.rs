@@ -0,0 +1,146 @@
+//! In-memory retention of recent console lines for crash dumps.
+
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::sync::Mutex;
+
+/// Number of complete lines a [`LogRing`] retains.
+pub const LOG_RING_LINES: usize = 16;
+/// Maximum number of bytes kept per line; longer lines are truncated.
+pub const LOG_RING_WIDTH: usize = 120;
+
+#[derive(Clone, Copy)]
+struct Line {
+    buf: [u8; LOG_RING_WIDTH],
+    len: usize,
+}
+
+impl Line {
+    const EMPTY: Self = Self {
+        buf: [0; LOG_RING_WIDTH],
+        len: 0,
+    };
+
+    fn as_str(&self) -> &str {
+        // Only whole `char`s are ever copied in, so this cannot fail.
+        core::str::from_utf8(&self.buf[..self.len]).unwrap_or("")
+    }
+}
+
+/// A fixed-size ring of the most recent output lines.
+///
+/// Text is split on `\n`; once [`LOG_RING_LINES`] complete lines are held,
+/// each new line drops the oldest one. A trailing partial line is kept aside
+/// until its newline arrives.
+#[derive(Clone)]
+pub struct LogRing {
+    lines: [Line; LOG_RING_LINES],
+    /// Index of the oldest complete line.
+    first: usize,
+    /// Number of complete lines held.
+    count: usize,
+    pending: Line,
+}
+
+impl LogRing {
+    /// Creates an empty ring.
+    pub const fn new() -> Self {
+        Self {
+            lines: [Line::EMPTY; LOG_RING_LINES],
+            first: 0,
+            count: 0,
+            pending: Line::EMPTY,
+        }
+    }
+
+    /// Discards all retained lines, including a partial one.
+    pub fn clear(&mut self) {
+        self.first = 0;
+        self.count = 0;
+        self.pending.len = 0;
+    }
+
+    /// Returns the complete lines held, oldest first, without their newlines.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        (0..self.count).map(move |i| self.lines[(self.first + i) % LOG_RING_LINES].as_str())
+    }
+
+    fn end_line(&mut self) {
+        if self.count == LOG_RING_LINES {
+            self.lines[self.first] = self.pending;
+            self.first = (self.first + 1) % LOG_RING_LINES;
+        } else {
+            self.lines[(self.first + self.count) % LOG_RING_LINES] = self.pending;
+            self.count += 1;
+        }
+        self.pending.len = 0;
+    }
+}
+
+impl Default for LogRing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Write for LogRing {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            if c == '\n' {
+                self.end_line();
+                continue;
+            }
+            let len = self.pending.len;
+            if len + c.len_utf8() <= LOG_RING_WIDTH {
+                c.encode_utf8(&mut self.pending.buf[len..]);
+                self.pending.len += c.len_utf8();
+            }
+        }
+        Ok(())
+    }
+}
+
+static LOG_CAPTURE: AtomicBool = AtomicBool::new(false);
+static LOG_RING: Mutex<LogRing> = Mutex::new(LogRing::new());
+
+/// Makes [`print!`], [`println!`] and the log macros also copy their output
+/// into the global [`LogRing`], for later retrieval with [`dump_log_ring!`].
+///
+/// Lines dropped by [`set_print_budget`](super::set_print_budget) are still
+/// captured. Disabled by default.
+///
+/// [`print!`]: crate::print
+/// [`println!`]: crate::println
+/// [`dump_log_ring!`]: crate::dump_log_ring
+pub fn set_log_capture(enabled: bool) {
+    LOG_CAPTURE.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether output is being captured into the global [`LogRing`].
+pub fn log_capture() -> bool {
+    LOG_CAPTURE.load(Ordering::Relaxed)
+}
+
+/// Discards everything captured in the global [`LogRing`].
+pub fn clear_log_ring() {
+    LOG_RING.lock().clear();
+}
+
+/// Appends already formatted text to the global [`LogRing`].
+///
+/// The lock is only held while copying `s`, never while user `Display` code
+/// runs, so output printed from within a `Display` impl cannot deadlock.
+pub(super) fn capture_str(s: &str) {
+    let _ = fmt::Write::write_str(&mut *LOG_RING.lock(), s);
+}
+
+#[doc(hidden)]
+pub fn __dump_log_ring() {
+    // Copy the lines out first, so that printing them neither deadlocks on
+    // the ring nor feeds them back into it.
+    let ring = LOG_RING.lock().clone();
+    for line in ring.lines() {
+        super::stdio::write_console(format_args!("{}\n", line));
+    }
+}
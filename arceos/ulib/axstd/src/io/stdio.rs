@@ -1,5 +1,8 @@
 use crate::io::{self, prelude::*, BufReader};
 use crate::sync::{Mutex, MutexGuard};
+use core::cell::Cell;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 #[cfg(feature = "alloc")]
 use alloc::{string::String, vec::Vec};
@@ -28,6 +31,7 @@ impl Write for StdoutRaw {
         arceos_api::stdio::ax_console_write_bytes(buf)
     }
     fn flush(&mut self) -> io::Result<()> {
+        // The console backends have no way to wait for their output to drain.
         Ok(())
     }
 }
@@ -161,13 +165,283 @@ pub fn stdout() -> Stdout {
     Stdout { inner: &INSTANCE }
 }
 
+/// Maximum number of lines allowed in the current print window.
+static PRINT_BUDGET: AtomicUsize = AtomicUsize::new(usize::MAX);
+/// Number of lines emitted in the current print window.
+static PRINT_USED: AtomicUsize = AtomicUsize::new(0);
+/// Number of lines dropped in the current print window.
+static PRINT_SUPPRESSED: AtomicUsize = AtomicUsize::new(0);
+/// Whether to flush the console after every line.
+static FLUSH_PER_LINE: AtomicBool = AtomicBool::new(false);
+
+/// Limits how many lines [`print!`] and [`println!`] may emit per print
+/// window, to avoid stalling on slow serial consoles.
+///
+/// Every print call counts as one line. Lines beyond the budget are dropped,
+/// and a `... N lines suppressed` summary is printed when the window is closed
+/// by [`end_print_window`] or by setting a new budget. Pass [`usize::MAX`] to
+/// disable throttling, which is the default.
+///
+/// [`print!`]: crate::print
+/// [`println!`]: crate::println
+pub fn set_print_budget(max_lines: usize) {
+    end_print_window();
+    PRINT_BUDGET.store(max_lines, Ordering::Relaxed);
+}
+
+/// Closes the current print window, reporting how many lines were dropped
+/// by [`set_print_budget`] and starting a new window with a fresh budget.
+pub fn end_print_window() {
+    let suppressed = PRINT_SUPPRESSED.swap(0, Ordering::Relaxed);
+    PRINT_USED.store(0, Ordering::Relaxed);
+    if suppressed > 0 {
+        write_console(format_args!("... {} lines suppressed\n", suppressed));
+    }
+}
+
+/// Makes [`print!`] and [`println!`] flush the console after every write
+/// that contains a newline.
+///
+/// The flush goes to the [`Stdout`] handle, whose `flush` does nothing yet:
+/// the console backends write every byte synchronously and have no drain to
+/// wait on. Until a backend implements flushing, this setting has no effect.
+/// Disabled by default.
+///
+/// [`print!`]: crate::print
+/// [`println!`]: crate::println
+pub fn set_flush_per_line(enabled: bool) {
+    FLUSH_PER_LINE.store(enabled, Ordering::Relaxed);
+}
+
+/// The sink behind the print macros.
+trait Console {
+    /// Writes formatted output to the console.
+    fn write_fmt(&mut self, args: fmt::Arguments);
+
+    /// Writes formatted output to the error sink.
+    ///
+    /// The default implementation shares the console with normal output.
+    fn write_err_fmt(&mut self, args: fmt::Arguments) {
+        self.write_fmt(args);
+    }
+
+    /// Waits until everything written so far has been sent out.
+    fn flush(&mut self) {}
+
+    /// Blocks until a key is pressed, discarding it.
+    fn wait_key(&mut self) {}
+
+    /// Writes as much of `s` as the device accepts without blocking,
+    /// returning the number of bytes taken.
+    ///
+    /// The default implementation writes everything.
+    fn try_write_str(&mut self, s: &str) -> usize {
+        self.write_fmt(format_args!("{}", s));
+        s.len()
+    }
+}
+
+#[cfg(not(test))]
+struct KernelConsole;
+
+#[cfg(not(test))]
+impl Console for KernelConsole {
+    fn write_fmt(&mut self, args: fmt::Arguments) {
+        if cfg!(feature = "smp") {
+            // synchronize using the lock in axlog, to avoid interleaving
+            // with kernel logs
+            arceos_api::stdio::ax_console_write_fmt(args).unwrap();
+        } else {
+            stdout().lock().write_fmt(args).unwrap();
+        }
+    }
+
+    fn flush(&mut self) {
+        stdout().lock().flush().unwrap();
+    }
+
+    fn wait_key(&mut self) {
+        let _ = stdin().read(&mut [0]);
+    }
+
+    fn try_write_str(&mut self, s: &str) -> usize {
+        arceos_api::stdio::ax_console_write_bytes(s.as_bytes()).unwrap_or(0)
+    }
+}
+
+#[cfg(not(test))]
+fn console() -> impl Console {
+    KernelConsole
+}
+
+#[cfg(test)]
+std::thread_local! {
+    /// Console output captured by unit tests instead of being printed.
+    pub(super) static CAPTURED: core::cell::RefCell<std::string::String> =
+        const { core::cell::RefCell::new(std::string::String::new()) };
+    /// Error sink output captured by unit tests instead of being printed.
+    pub(super) static CAPTURED_ERR: core::cell::RefCell<std::string::String> =
+        const { core::cell::RefCell::new(std::string::String::new()) };
+    /// Number of console flushes seen by unit tests.
+    pub(super) static FLUSHES: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+    /// Free space in the FIFO of the console mocked by unit tests.
+    pub(super) static FIFO_ROOM: core::cell::Cell<usize> = const { core::cell::Cell::new(usize::MAX) };
+}
+
+#[cfg(test)]
+struct TestConsole;
+
+#[cfg(test)]
+impl Console for TestConsole {
+    fn write_fmt(&mut self, args: fmt::Arguments) {
+        CAPTURED.with(|c| fmt::Write::write_fmt(&mut *c.borrow_mut(), args).unwrap());
+    }
+
+    fn write_err_fmt(&mut self, args: fmt::Arguments) {
+        CAPTURED_ERR.with(|c| fmt::Write::write_fmt(&mut *c.borrow_mut(), args).unwrap());
+    }
+
+    fn flush(&mut self) {
+        FLUSHES.with(|f| f.set(f.get() + 1));
+    }
+
+    fn try_write_str(&mut self, s: &str) -> usize {
+        let room = FIFO_ROOM.with(|r| r.get());
+        let mut n = s.len().min(room);
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        CAPTURED.with(|c| c.borrow_mut().push_str(&s[..n]));
+        FIFO_ROOM.with(|r| r.set(room - n));
+        n
+    }
+}
+
+#[cfg(test)]
+fn console() -> impl Console {
+    TestConsole
+}
+
+/// Passes `args` through to the console, noting whether they wrote a newline
+/// so that [`emit`] can decide to flush without formatting them again, and
+/// copying the text into the log ring if `capture` is set.
+struct Tee<'a> {
+    args: fmt::Arguments<'a>,
+    capture: bool,
+    newline: Cell<bool>,
+}
+
+impl fmt::Display for Tee<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Writer<'a, 'b, 'c> {
+            f: &'a mut fmt::Formatter<'b>,
+            tee: &'a Tee<'c>,
+        }
+
+        impl fmt::Write for Writer<'_, '_, '_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                if s.contains('\n') {
+                    self.tee.newline.set(true);
+                }
+                if self.tee.capture {
+                    super::ring::capture_str(s);
+                }
+                self.f.write_str(s)
+            }
+        }
+
+        fmt::write(&mut Writer { f, tee: self }, self.args)
+    }
+}
+
+/// Writes `args` to the console, or to its error sink if `err` is set,
+/// formatting them exactly once. With `capture`, the text is also copied
+/// into the log ring.
+fn emit(args: fmt::Arguments, err: bool, capture: bool) {
+    let tee = Tee {
+        args,
+        capture,
+        newline: Cell::new(false),
+    };
+    let mut console = console();
+    if err {
+        console.write_err_fmt(format_args!("{}", tee));
+    } else {
+        console.write_fmt(format_args!("{}", tee));
+    }
+    if FLUSH_PER_LINE.load(Ordering::Relaxed) && tee.newline.get() {
+        console.flush();
+    }
+}
+
+/// Blocks until a key is pressed on the console, discarding it.
+pub(super) fn wait_key() {
+    console().wait_key();
+}
+
+pub(super) fn write_console(args: fmt::Arguments) {
+    emit(args, false, false);
+}
+
+/// Prints `args` without blocking, returning how many bytes the console
+/// accepted.
+///
+/// Output stops at the first short write, so a result smaller than the
+/// formatted length means the rest was not sent and may be retried. Unlike
+/// [`print!`], this bypasses the budget set by [`set_print_budget`].
+///
+/// [`print!`]: crate::print
+pub fn try_print(args: fmt::Arguments) -> usize {
+    struct Sink<C> {
+        console: C,
+        written: usize,
+    }
+
+    impl<C: Console> fmt::Write for Sink<C> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let n = self.console.try_write_str(s);
+            self.written += n;
+            if n < s.len() {
+                Err(fmt::Error)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    let mut sink = Sink {
+        console: console(),
+        written: 0,
+    };
+    let _ = fmt::write(&mut sink, args);
+    sink.written
+}
+
 #[doc(hidden)]
 pub fn __print_impl(args: core::fmt::Arguments) {
-    if cfg!(feature = "smp") {
-        // synchronize using the lock in axlog, to avoid interleaving
-        // with kernel logs
-        arceos_api::stdio::ax_console_write_fmt(args).unwrap();
-    } else {
-        stdout().lock().write_fmt(args).unwrap();
+    let capture = super::ring::log_capture();
+    let budget = PRINT_BUDGET.load(Ordering::Relaxed);
+    if budget != usize::MAX && PRINT_USED.fetch_add(1, Ordering::Relaxed) >= budget {
+        PRINT_SUPPRESSED.fetch_add(1, Ordering::Relaxed);
+        // Dropped lines are still captured, formatted straight into the ring.
+        if capture {
+            struct Ring;
+
+            impl fmt::Write for Ring {
+                fn write_str(&mut self, s: &str) -> fmt::Result {
+                    super::ring::capture_str(s);
+                    Ok(())
+                }
+            }
+
+            let _ = fmt::write(&mut Ring, args);
+        }
+        return;
     }
+    emit(args, false, capture);
+}
+
+#[doc(hidden)]
+pub fn __eprint_impl(args: core::fmt::Arguments) {
+    emit(args, true, super::ring::log_capture());
 }
@@ -0,0 +1,1242 @@
+use std::format;
+use std::string::{String, ToString};
+use std::sync::Mutex;
+
+use super::cpu::TEST_CPU_ID;
+use super::stdio::{CAPTURED, CAPTURED_ERR, FIFO_ROOM, FLUSHES};
+use super::*;
+
+/// Serializes tests that touch the global print configuration.
+static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Runs `f` and returns everything it printed.
+fn capture(f: impl FnOnce()) -> String {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    CAPTURED.with(|c| c.borrow_mut().clear());
+    CAPTURED_ERR.with(|c| c.borrow_mut().clear());
+    FLUSHES.with(|f| f.set(0));
+    FIFO_ROOM.with(|r| r.set(usize::MAX));
+    set_flush_per_line(false);
+    set_print_budget(usize::MAX);
+    set_color_enabled(true);
+    set_log_level(LogLevel::Trace);
+    set_bell_on_error(false);
+    set_log_capture(false);
+    clear_log_ring();
+    for cpu in 0..arceos_api::config::SMP {
+        TEST_CPU_ID.with(|id| id.set(cpu));
+        set_current_cpu(cpu);
+    }
+    TEST_CPU_ID.with(|id| id.set(0));
+    set_dump_pause(DumpPause::Key);
+    while indent_level() > 0 {
+        dedent();
+    }
+    set_indent_unit("  ");
+    set_terminal_width(80);
+    f();
+    CAPTURED.with(|c| c.take())
+}
+
+#[test]
+fn print_budget_suppresses_excess_lines() {
+    let out = capture(|| {
+        set_print_budget(3);
+        for i in 0..10 {
+            println!("line {}", i);
+        }
+        set_print_budget(usize::MAX);
+    });
+    assert_eq!(out, "line 0\nline 1\nline 2\n... 7 lines suppressed\n");
+}
+
+#[test]
+fn print_budget_resets_per_window() {
+    let out = capture(|| {
+        set_print_budget(1);
+        println!("a");
+        println!("b");
+        end_print_window();
+        println!("c");
+        set_print_budget(usize::MAX);
+        println!("d");
+    });
+    assert_eq!(out, "a\n... 1 lines suppressed\nc\nd\n");
+}
+
+#[test]
+fn grouped_binary() {
+    assert_eq!(GroupedBinary::new(0xacu8, 4).to_string(), "1010_1100");
+    assert_eq!(GroupedBinary::new(0x5u8, 8).to_string(), "00000101");
+    assert_eq!(
+        GroupedBinary::new(0xdead_beefu32, 4).to_string(),
+        "1101_1110_1010_1101_1011_1110_1110_1111"
+    );
+    assert_eq!(
+        GroupedBinary::new(0x1u32, 8).to_string(),
+        "00000000_00000000_00000000_00000001"
+    );
+
+    let out = capture(|| bin_println!(0xf0u8, 4));
+    assert_eq!(out, "1111_0000\n");
+}
+
+#[test]
+fn log_err_passes_result_through() {
+    fn parse(s: &str) -> core::result::Result<u32, core::num::ParseIntError> {
+        s.parse()
+    }
+
+    let out = capture(|| assert_eq!(log_err!(parse("42")), Ok(42)));
+    assert_eq!(out, "");
+
+    let out = capture(|| assert!(log_err!(parse("x")).is_err()));
+    assert!(out.starts_with("\x1b[31m[ERROR] parse(\"x\"): ParseIntError"));
+    assert!(out.ends_with("\x1b[0m\n"));
+}
+
+#[test]
+fn buffer_diff_highlights_differences() {
+    let a = [0x00, 0x11, 0x22, 0x33];
+    let b = [0x00, 0x11, 0xff, 0x33];
+    let out = capture(|| print_buffer_diff(&a, &b));
+    let pad = " ".repeat(12 * 3);
+    assert_eq!(
+        out,
+        format!("00000000  00 11 \x1b[31m22\x1b[0m 33{pad}  | 00 11 \x1b[31mff\x1b[0m 33{pad}\n")
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        print_buffer_diff(&a, &b);
+    });
+    assert_eq!(
+        out,
+        format!("00000000  00 11 22 33{pad}  | 00 11 ff 33{pad}\n")
+    );
+}
+
+#[test]
+fn buffer_diff_marks_excess_bytes() {
+    let a = [0xaa; 18];
+    let b = [0xaa; 16];
+    let out = capture(|| {
+        set_color_enabled(false);
+        print_buffer_diff(&a, &b);
+    });
+    let lines: std::vec::Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], format!("00000000 {0}  |{0}", " aa".repeat(16)));
+    assert_eq!(
+        lines[1],
+        format!("00000010  aa aa{0}  | -- --{0}", "   ".repeat(14))
+    );
+
+    let out = capture(|| print_buffer_diff(&a, &b));
+    assert!(out.contains("\x1b[31maa\x1b[0m \x1b[31maa\x1b[0m"));
+}
+
+#[test]
+fn dump_fields_colors_and_indents() {
+    let out = capture(
+        || dump_fields!("Desc", addr: "{:#x}" = 0x8020_0000usize, len = 64, flags = [1, 2]),
+    );
+    assert_eq!(
+        out,
+        "\x1b[1mDesc\x1b[0m\n\
+         \x20 \x1b[36maddr\x1b[0m = 0x80200000\n\
+         \x20 \x1b[36mlen\x1b[0m = 64\n\
+         \x20 \x1b[36mflags\x1b[0m = [1, 2]\n"
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        dump_fields!("Desc", len = 64);
+    });
+    assert_eq!(out, "Desc\n  len = 64\n");
+}
+
+#[test]
+fn history_table_rows() {
+    struct Stats {
+        used_bytes: usize,
+        used_pages: usize,
+    }
+    let history = [
+        (
+            "boot",
+            Stats {
+                used_bytes: 0,
+                used_pages: 0,
+            },
+        ),
+        (
+            "heap",
+            Stats {
+                used_bytes: 4096,
+                used_pages: 0,
+            },
+        ),
+        (
+            "paging",
+            Stats {
+                used_bytes: 4096,
+                used_pages: 12,
+            },
+        ),
+    ];
+
+    let out = capture(|| history_println!(history));
+    assert_eq!(
+        out,
+        "label              used_bytes used_pages\n\
+         boot                        0          0\n\
+         heap                     4096          0\n\
+         paging                   4096         12\n"
+    );
+}
+
+#[test]
+fn link_escape_and_fallback() {
+    let url = "https://arceos-org.github.io/arceos";
+    let out = capture(|| link_println!(url, "ArceOS {}", "docs"));
+    assert_eq!(
+        out,
+        "\x1b]8;;https://arceos-org.github.io/arceos\x1b\\ArceOS docs\x1b]8;;\x1b\\\n"
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        link_println!(url, "ArceOS docs");
+    });
+    assert_eq!(out, "ArceOS docs (https://arceos-org.github.io/arceos)\n");
+}
+
+#[test]
+fn tree_connectors() {
+    const TREE: TreeNode = TreeNode::new(
+        "memory",
+        &[
+            TreeNode::colored(
+                "ram",
+                crate::ColorCode::Green,
+                &[TreeNode::new("kernel", &[]), TreeNode::new("heap", &[])],
+            ),
+            TreeNode::new("mmio", &[TreeNode::new("uart", &[])]),
+        ],
+    );
+
+    let out = capture(|| print_tree(&TREE));
+    assert_eq!(
+        out,
+        "memory\n\
+         ├── \x1b[32mram\x1b[0m\n\
+         │   ├── kernel\n\
+         │   └── heap\n\
+         └── mmio\n\
+         \x20   └── uart\n"
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        print_tree(&TREE);
+    });
+    assert_eq!(
+        out,
+        "memory\n\
+         |-- ram\n\
+         |   |-- kernel\n\
+         |   `-- heap\n\
+         `-- mmio\n\
+         \x20   `-- uart\n"
+    );
+}
+
+#[test]
+fn soft_assert_counts_failures() {
+    let before = soft_assert_failures();
+    let out = capture(|| {
+        assert!(soft_assert!(1 + 1 == 2));
+        assert!(soft_assert!(true, "never printed"));
+    });
+    assert_eq!(out, "");
+    assert_eq!(soft_assert_failures(), before);
+
+    let out = capture(|| {
+        let irq = 3;
+        assert!(!soft_assert!(irq < 2, "irq {} out of range", irq));
+    });
+    assert_eq!(soft_assert_failures(), before + 1);
+    assert!(out.starts_with("\x1b[31m[WARN] "));
+    assert!(out.contains("soft assertion failed: irq < 2: irq 3 out of range"));
+}
+
+#[test]
+fn step_markers() {
+    let out = capture(|| {
+        step_println!(1, 12, "init heap");
+        step_println!(6, 12, "init drivers");
+        step_println!(12, 12, "done");
+    });
+    assert_eq!(
+        out,
+        "\x1b[33m[ 1/12]\x1b[0m init heap\n\
+         \x1b[92m[ 6/12]\x1b[0m init drivers\n\
+         \x1b[32m[12/12]\x1b[0m done\n"
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        step_println!(3, 100, "plain");
+    });
+    assert_eq!(out, "[  3/100] plain\n");
+}
+
+#[test]
+fn diff_println_cases() {
+    let a = *b"ArceOS";
+    let out = capture(|| diff_println!(a, a));
+    assert!(!out.contains("\x1b[31m"));
+    assert_eq!(out.lines().count(), 1);
+
+    let mut b = a;
+    b[3] = b'E';
+    let out = capture(|| diff_println!(a, b));
+    assert_eq!(out.matches("\x1b[31m").count(), 2);
+    assert!(out.contains("\x1b[31m65\x1b[0m"));
+    assert!(out.contains("\x1b[31m45\x1b[0m"));
+
+    let out = capture(|| diff_println!(a, b"Arc"));
+    assert_eq!(out.matches("\x1b[31m").count(), 3);
+    assert_eq!(out.matches(" --").count(), 3);
+}
+
+#[test]
+fn width_skips_escapes() {
+    assert_eq!(display_width(""), 0);
+    assert_eq!(display_width("ArceOS"), 6);
+    assert_eq!(display_width("café naïve"), 10);
+    assert_eq!(display_width("\x1b[31mred\x1b[0m"), 3);
+    assert_eq!(
+        display_width("\x1b]8;;https://x\x1b\\link\x1b]8;;\x1b\\"),
+        4
+    );
+    assert_eq!(display_width("\x1b[1;32mé\x1b[0m!"), 2);
+    assert_eq!(display_width("内核 boot"), 9);
+}
+
+#[test]
+fn truncate_keeps_whole_chars() {
+    assert_eq!(truncate_str("café au lait", 4), "café");
+    assert_eq!(truncate_str("short", 10), "short");
+    assert_eq!(
+        truncate_str("\x1b[31mrouge\x1b[0m", 5),
+        "\x1b[31mrouge\x1b[0m"
+    );
+    assert_eq!(truncate_str("\x1b[31mrouge\x1b[0m", 2), "\x1b[31mro");
+    assert_eq!(truncate_str("内核日志", 5), "内核");
+
+    let mut buf = [0u8; 16];
+    let n = truncate_to_width("naïve", 3, &mut buf);
+    assert_eq!(&buf[..n], "naï".as_bytes());
+
+    // a buffer that only fits half of "ï" must not split it
+    let mut small = [0u8; 3];
+    let n = truncate_to_width("naïve", 3, &mut small);
+    assert_eq!(&small[..n], b"na");
+}
+
+#[test]
+fn count_pluralization() {
+    let out = capture(|| {
+        for n in 0..3 {
+            count_println!(n, "file", "files");
+        }
+        count_println!(2usize, "entry", "entries");
+        count_println!(1u8, "CPU");
+        count_println!(4u64, "CPU");
+    });
+    assert_eq!(out, "0 files\n1 file\n2 files\n2 entries\n1 CPU\n4 CPUs\n");
+}
+
+#[test]
+fn backtrace_frames() {
+    let out = capture(|| {
+        print_backtrace_frame(
+            0,
+            0xffff_ffc0_8020_1a2c,
+            Some("axruntime::lang_items::panic"),
+        );
+        print_backtrace_frame(1, 0x8020_0000, None);
+    });
+    assert_eq!(
+        out,
+        "\x1b[2m#0 \x1b[0m  \x1b[36m0xffffffc080201a2c\x1b[0m  axruntime::lang_items::panic\n\
+         \x1b[2m#1 \x1b[0m  \x1b[36m0x0000000080200000\x1b[0m  <unknown>\n"
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        print_backtrace_frame(12, 0x1000, Some("main"));
+    });
+    assert_eq!(out, "#12  0x0000000000001000  main\n");
+}
+
+#[test]
+fn byte_sizes() {
+    let cases: [(u64, &str); 9] = [
+        (0, "0 B"),
+        (1023, "1023 B"),
+        (1024, "1.00 KiB"),
+        (1536, "1.50 KiB"),
+        (10 * 1024 + 5, "10.00 KiB"),
+        (1024 * 1024 - 1, "1.00 MiB"),
+        (5 * 1024 * 1024 + 300 * 1024, "5.29 MiB"),
+        (1 << 30, "1.00 GiB"),
+        (3 << 40, "3072.00 GiB"),
+    ];
+    for (bytes, expected) in cases {
+        assert_eq!(ByteSize(bytes).to_string(), expected);
+    }
+
+    let out = capture(|| crate::size_println!("heap used", 2048usize));
+    assert_eq!(out, "heap used: 2.00 KiB\n");
+}
+
+#[test]
+fn color_format_into_buffer() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    set_color_enabled(true);
+
+    let mut buf = [0u8; 64];
+    let n = crate::color_format!(&mut buf, crate::ColorCode::Green, "ok {}", 42).unwrap();
+    assert_eq!(&buf[..n], b"\x1b[32mok 42\x1b[0m");
+
+    let mut small = [0u8; 8];
+    let res = crate::color_format!(&mut small, crate::ColorCode::Green, "ok {}", 42);
+    assert!(res.is_err());
+    assert_eq!(&small, b"\x1b[32mok ");
+
+    set_color_enabled(false);
+    let n = crate::color_format!(&mut buf, crate::ColorCode::Red, "plain").unwrap();
+    assert_eq!(&buf[..n], b"plain");
+    set_color_enabled(true);
+}
+
+#[test]
+fn threshold_bands() {
+    let line =
+        |value: u32| capture(|| crate::threshold_println!(value, 70, 90, "usage {}%", value));
+    assert_eq!(line(0), "\x1b[32musage 0%\x1b[0m\n");
+    assert_eq!(line(69), "\x1b[32musage 69%\x1b[0m\n");
+    assert_eq!(line(70), "\x1b[33musage 70%\x1b[0m\n");
+    assert_eq!(line(89), "\x1b[33musage 89%\x1b[0m\n");
+    assert_eq!(line(90), "\x1b[31musage 90%\x1b[0m\n");
+    assert_eq!(line(100), "\x1b[31musage 100%\x1b[0m\n");
+}
+
+#[test]
+fn spinner_frames_cycle() {
+    let mut state = 0;
+    let out = capture(|| {
+        for _ in 0..6 {
+            crate::spinner!(state);
+        }
+        crate::spinner_done!();
+    });
+    assert_eq!(out, "\r|\r/\r-\r\\\r|\r/\r \r");
+    assert_eq!(state, 6);
+    assert_eq!(spinner_frame(7), '\\');
+}
+
+#[test]
+fn log_level_filters_messages() {
+    let out = capture(|| {
+        set_log_level(LogLevel::Warn);
+        crate::error!("disk {} missing", 0);
+        crate::warn!("slow");
+        crate::info!("booted");
+        crate::debug!("x = {}", 1);
+        crate::trace!("enter");
+        set_log_level(LogLevel::Trace);
+    });
+    assert_eq!(
+        out,
+        "\x1b[31m[ERROR] disk 0 missing\x1b[0m\n\x1b[33m[WARN] slow\x1b[0m\n"
+    );
+
+    let out = capture(|| {
+        crate::info!("booted");
+        crate::trace!("enter");
+    });
+    assert_eq!(
+        out,
+        "\x1b[32m[INFO] booted\x1b[0m\n\x1b[90m[TRACE] enter\x1b[0m\n"
+    );
+    assert_eq!(log_level(), LogLevel::Trace);
+}
+
+#[test]
+fn hex_values_padded_to_type_width() {
+    let out = capture(|| {
+        crate::hex_println!("scause", 0x5u32);
+        crate::hex_println!("sepc", 0x8020_1a2cu64);
+    });
+    assert_eq!(
+        out,
+        "scause: \x1b[2m0x\x1b[0m\x1b[36m00000005\x1b[0m\n\
+         sepc: \x1b[2m0x\x1b[0m\x1b[36m0000000080201a2c\x1b[0m\n"
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        crate::hex_println!("a", 0xabu8);
+        crate::hex_println!("b", -1i16);
+    });
+    assert_eq!(out, "a: 0xab\nb: 0xffff\n");
+}
+
+#[test]
+fn hex_values_in_chosen_color() {
+    let out = capture(|| {
+        crate::hex_println!("stval", 0xdeadu16, crate::ColorCode::BrightRed);
+        crate::println!("{}", PaddedHex::new(0x7u8));
+    });
+    assert_eq!(
+        out,
+        "stval: \x1b[2m0x\x1b[0m\x1b[91mdead\x1b[0m\n\
+         \x1b[2m0x\x1b[0m\x1b[36m07\x1b[0m\n"
+    );
+}
+
+#[test]
+fn enum_variants_with_discriminants() {
+    use crate::ColorCode::*;
+
+    let out = capture(|| {
+        for color in [
+            Black,
+            Red,
+            Green,
+            Yellow,
+            Blue,
+            Magenta,
+            Cyan,
+            White,
+            BrightBlack,
+            BrightRed,
+            BrightGreen,
+            BrightYellow,
+            BrightBlue,
+            BrightMagenta,
+            BrightCyan,
+            BrightWhite,
+        ] {
+            crate::enum_println!(color);
+        }
+    });
+    assert_eq!(
+        out,
+        "Black(30)\nRed(31)\nGreen(32)\nYellow(33)\nBlue(34)\nMagenta(35)\nCyan(36)\n\
+         White(37)\nBrightBlack(90)\nBrightRed(91)\nBrightGreen(92)\nBrightYellow(93)\n\
+         BrightBlue(94)\nBrightMagenta(95)\nBrightCyan(96)\nBrightWhite(97)\n"
+    );
+}
+
+#[test]
+fn centered_messages() {
+    let out = capture(|| {
+        crate::center_println!(10, "boot");
+        crate::center_println!(10, "abc");
+        crate::center_println!(4, "too long");
+        crate::center_println!(8, "{}", Colored::new(crate::ColorCode::Red, "内核"));
+    });
+    assert_eq!(
+        out,
+        "   boot   \n   abc    \ntoo long\n  \x1b[31m内核\x1b[0m  \n"
+    );
+}
+
+#[test]
+fn boxed_messages() {
+    let out = capture(|| crate::box_println!(crate::ColorCode::Yellow, "{} ready", "disk"));
+    assert_eq!(
+        out,
+        "\x1b[33m┌────────────┐\x1b[0m\n\
+         \x1b[33m│ disk ready │\x1b[0m\n\
+         \x1b[33m└────────────┘\x1b[0m\n"
+    );
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        crate::box_println!(crate::ColorCode::Red, "PANIC\nsee log above");
+    });
+    assert_eq!(
+        out,
+        "+---------------+\n\
+         | PANIC         |\n\
+         | see log above |\n\
+         +---------------+\n"
+    );
+}
+
+#[test]
+fn bell_before_errors() {
+    let out = capture(|| {
+        set_bell_on_error(true);
+        crate::error!("boom");
+        crate::warn!("no bell");
+        set_color_enabled(false);
+        crate::error!("quiet");
+        set_bell_on_error(false);
+    });
+    assert_eq!(
+        out,
+        "\x07\x1b[31m[ERROR] boom\x1b[0m\n\x1b[33m[WARN] no bell\x1b[0m\n[ERROR] quiet\n"
+    );
+
+    let out = capture(|| crate::error!("boom"));
+    assert_eq!(out, "\x1b[31m[ERROR] boom\x1b[0m\n");
+}
+
+#[test]
+fn require_prints_only_on_failure() {
+    let mut evals = 0;
+    let mut check = |v: bool| {
+        evals += 1;
+        v
+    };
+
+    let mut ok = false;
+    let out = capture(|| {
+        ok = crate::require!(check(true), crate::ColorCode::Yellow, "missing {}", "rtc");
+    });
+    assert!(ok);
+    assert_eq!(out, "");
+
+    let out = capture(|| {
+        ok = crate::require!(check(false), crate::ColorCode::Yellow, "missing {}", "rtc");
+    });
+    assert!(!ok);
+    assert_eq!(out, "\x1b[33mmissing rtc\x1b[0m\n");
+    assert_eq!(evals, 2);
+}
+
+#[test]
+fn print_only_on_change() {
+    let out = capture(|| {
+        for temp in [40, 40, 40, 41, 41, 40] {
+            crate::println_on_change!(TEMP, temp, "temp {}", temp);
+        }
+    });
+    assert_eq!(out, "temp 40\ntemp 41\ntemp 40\n");
+}
+
+#[test]
+fn deprecated_warns_once_per_call_site() {
+    let old_path = || crate::deprecated!("use {} instead", "map_region");
+    let out = capture(|| {
+        for _ in 0..3 {
+            old_path();
+        }
+        crate::println_once!("other site");
+        crate::println_once!("other site");
+    });
+    let line = line!() - 8;
+    assert_eq!(
+        out,
+        format!(
+            "\x1b[33m[DEPRECATED] {}:{}: use map_region instead\x1b[0m\nother site\nother site\n",
+            file!(),
+            line
+        )
+    );
+}
+
+#[test]
+fn print_formats_arguments_once() {
+    struct Counted<'a>(&'a core::cell::Cell<usize>);
+
+    impl core::fmt::Display for Counted<'_> {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.0.set(self.0.get() + 1);
+            f.write_str("x")
+        }
+    }
+
+    let calls = core::cell::Cell::new(0);
+    let out = capture(|| {
+        set_flush_per_line(true);
+        println!("{}", Counted(&calls));
+        crate::eprintln!("{}", Counted(&calls));
+    });
+    assert_eq!(out, "x\n");
+    assert_eq!(calls.get(), 2);
+}
+
+#[test]
+fn flush_per_line() {
+    let flushes = || FLUSHES.with(|f| f.get());
+
+    let mut counts = (0, 0);
+    capture(|| {
+        println!("a");
+        crate::color_println!(crate::ColorCode::Green, "b");
+        counts.0 = flushes();
+
+        set_flush_per_line(true);
+        println!("a");
+        crate::color_println!(crate::ColorCode::Green, "b");
+        print!("no newline");
+        counts.1 = flushes();
+        set_flush_per_line(false);
+    });
+    assert_eq!(counts, (0, 2));
+}
+
+#[test]
+fn composed_styles() {
+    use crate::ColorCode::{Blue, Red};
+
+    let style = StyleBuilder::new().fg(Red).bg(Blue).bold().underline();
+    let out = capture(|| crate::style_println!(style, "alert {}", 1));
+    assert_eq!(out, "\x1b[31;44;1;4malert 1\x1b[0m\n");
+
+    let out = capture(|| {
+        crate::style_println!(StyleBuilder::new(), "plain");
+        set_color_enabled(false);
+        crate::style_println!(style, "alert");
+    });
+    assert_eq!(out, "plain\nalert\n");
+}
+
+#[test]
+fn gauges() {
+    let out = capture(|| {
+        crate::gauge_println!("heap", 0);
+        crate::gauge_println!("heap", 623);
+        crate::gauge_println!("heap", 1000);
+        crate::gauge_println!("heap", 1500);
+    });
+    assert_eq!(
+        out,
+        "heap [\x1b[32m\x1b[0m··········] 0.0%\n\
+         heap [\x1b[32m██████\x1b[0m····] 62.3%\n\
+         heap [\x1b[31m██████████\x1b[0m] 100.0%\n\
+         heap [\x1b[31m██████████\x1b[0m] 100.0%\n"
+    );
+    assert_eq!(Gauge::new(700).color(), crate::ColorCode::Yellow);
+
+    let out = capture(|| {
+        set_color_enabled(false);
+        crate::gauge_println!("heap", 623);
+    });
+    assert_eq!(out, "heap 62.3%\n");
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn debug_println_in_debug_builds() {
+    let mut evals = 0;
+    let out = capture(|| {
+        crate::debug_println!("x = {}", {
+            evals += 1;
+            7
+        })
+    });
+    assert_eq!(out, "x = 7\n");
+    assert_eq!(evals, 1);
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn debug_println_in_release_builds() {
+    let mut evals = 0;
+    let out = capture(|| {
+        crate::debug_println!("x = {}", {
+            evals += 1;
+            7
+        })
+    });
+    assert_eq!(out, "");
+    assert_eq!(evals, 0);
+}
+
+#[test]
+fn try_print_reports_short_writes() {
+    let mut written = (0, 0);
+    let out = capture(|| {
+        written.0 = try_print(format_args!("id={} ", 42));
+        FIFO_ROOM.with(|r| r.set(5));
+        written.1 = try_print(format_args!("status={}", "ready"));
+    });
+    assert_eq!(written, (6, 5));
+    assert_eq!(out, "id=42 statu");
+}
+
+#[test]
+fn log_ring_capture_allows_nested_prints() {
+    struct Noisy;
+
+    impl core::fmt::Display for Noisy {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            crate::eprintln!("inner");
+            f.write_str("outer")
+        }
+    }
+
+    let out = capture(|| {
+        set_log_capture(true);
+        println!("{}", Noisy);
+        set_log_capture(false);
+        CAPTURED.with(|c| c.borrow_mut().clear());
+        crate::dump_log_ring!();
+    });
+    assert_eq!(out, "inner\nouter\n");
+}
+
+#[test]
+fn log_ring_keeps_most_recent_lines() {
+    let out = capture(|| {
+        set_log_capture(true);
+        for i in 0..LOG_RING_LINES + 4 {
+            println!("line {}", i);
+        }
+        crate::info!("done");
+        set_log_capture(false);
+        CAPTURED.with(|c| c.borrow_mut().clear());
+        crate::dump_log_ring!();
+    });
+    let expected: String = (5..LOG_RING_LINES + 4)
+        .map(|i| format!("line {}\n", i))
+        .chain(["\u{1b}[32m[INFO] done\u{1b}[0m\n".into()])
+        .collect();
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn log_ring_splits_and_truncates_lines() {
+    use core::fmt::Write as _;
+
+    let mut ring = LogRing::new();
+    let long = "x".repeat(LOG_RING_WIDTH + 10);
+    write!(ring, "a\nb").unwrap();
+    assert_eq!(ring.lines().collect::<Vec<_>>(), ["a"]);
+    write!(ring, "c\n{}\n", long).unwrap();
+    assert_eq!(
+        ring.lines().collect::<Vec<_>>(),
+        ["a", "bc", &long[..LOG_RING_WIDTH]]
+    );
+}
+
+#[test]
+fn bsp_println_only_on_boot_cpu() {
+    let out = capture(|| {
+        crate::bsp_println!("boot {}", 0);
+        set_is_bsp(false);
+        crate::bsp_println!("hidden");
+        set_current_cpu(2);
+        crate::bsp_println!("hidden");
+        set_current_cpu(0);
+        crate::bsp_println!("cpu {}", 0);
+    });
+    assert_eq!(out, "boot 0\ncpu 0\n");
+}
+
+#[test]
+fn bsp_flag_is_per_core() {
+    let out = capture(|| {
+        // An application processor clears its own flag while bringing itself up.
+        let ap = std::thread::spawn(|| {
+            TEST_CPU_ID.with(|id| id.set(1));
+            set_current_cpu(1);
+            crate::bsp_println!("hidden");
+            assert!(!is_bsp());
+            CAPTURED.with(|c| c.take())
+        });
+        assert_eq!(ap.join().unwrap(), "");
+        // The boot processor is unaffected.
+        assert!(is_bsp());
+        crate::bsp_println!("boot");
+    });
+    assert_eq!(out, "boot\n");
+}
+
+#[test]
+fn countdown_println_overwrites_line() {
+    let out = capture(|| {
+        for remaining in (0..=10u32).rev().step_by(5) {
+            crate::countdown_println!(remaining);
+        }
+    });
+    assert_eq!(
+        out,
+        "\rBooting in 10... \rBooting in 5... \rBooting in 0... \n"
+    );
+}
+
+#[test]
+fn columns_println_fills_rows() {
+    let out = capture(|| {
+        set_color_enabled(false);
+        crate::columns_println!(2, ["fpu" => true, "sse" => true, "avx" => false, "cores" => 4]);
+    });
+    assert_eq!(out, "fpu: true   sse: true\navx: false  cores: 4\n");
+}
+
+#[test]
+fn columns_println_partial_last_row() {
+    let out = capture(|| {
+        crate::columns_println!(2, ["a" => 1, "bb" => 22, "c" => 3]);
+    });
+    assert_eq!(
+        out,
+        "\u{1b}[36ma\u{1b}[0m: \u{1b}[33m1\u{1b}[0m    \
+         \u{1b}[36mbb\u{1b}[0m: \u{1b}[33m22\u{1b}[0m\n\
+         \u{1b}[36mc\u{1b}[0m: \u{1b}[33m3\u{1b}[0m\n"
+    );
+}
+
+#[test]
+fn logfmt_quotes_values_with_spaces() {
+    let out = capture(|| {
+        crate::logfmt!(LogLevel::Info, "msg" => "started", "port" => 8080);
+        crate::logfmt!(LogLevel::Warn, "msg" => "link down", "why" => "say \"hi\"", "x" => "");
+        set_log_level(LogLevel::Info);
+        crate::logfmt!(LogLevel::Debug, "msg" => "hidden");
+        crate::logfmt!(LogLevel::Error);
+    });
+    assert_eq!(
+        out,
+        "level=info msg=started port=8080\n\
+         level=warn msg=\"link down\" why=\"say \\\"hi\\\"\" x=\"\"\n\
+         level=error\n"
+    );
+}
+
+/// Runs `f` and returns what it printed to the standard output and to the
+/// error sink.
+fn capture_both(f: impl FnOnce()) -> (String, String) {
+    let mut err = String::new();
+    let out = capture(|| {
+        f();
+        err = CAPTURED_ERR.with(|c| c.take());
+    });
+    (out, err)
+}
+
+#[test]
+fn log_err_routes_levels() {
+    let (out, err) = capture_both(|| {
+        log_err!(LogLevel::Error, "disk {}", 0);
+        log_err!(LogLevel::Warn, "slow");
+        log_err!(LogLevel::Info, "ok");
+    });
+    assert_eq!(
+        err,
+        "\x1b[31m[ERROR] disk 0\x1b[0m\n\x1b[33m[WARN] slow\x1b[0m\n"
+    );
+    assert_eq!(out, "\x1b[32m[INFO] ok\x1b[0m\n");
+}
+
+#[test]
+fn log_err_honors_level_and_color() {
+    let (out, err) = capture_both(|| {
+        set_color_enabled(false);
+        set_log_level(LogLevel::Error);
+        log_err!(LogLevel::Error, "fatal");
+        log_err!(LogLevel::Warn, "hidden");
+        log_err!(LogLevel::Info, "hidden");
+    });
+    assert_eq!(err, "[ERROR] fatal\n");
+    assert_eq!(out, "");
+}
+
+#[test]
+fn regs_println_aligns_columns() {
+    let (sp, pc, status) = (0x8020_0000usize, 0x8020_1a2cu64, 42u32);
+    let out = capture(|| crate::regs_println!("sp" => sp, "pc" => pc, "status" => status));
+    assert_eq!(
+        out,
+        "sp      0x0000000080200000            2149580800\n\
+         pc      0x0000000080201a2c            2149587500\n\
+         status  0x000000000000002a                    42\n"
+    );
+}
+
+#[test]
+fn scaled_println_picks_prefix() {
+    let out = capture(|| {
+        crate::scaled_println!("a", 1023, "B");
+        crate::scaled_println!("b", 1024, "B");
+        crate::scaled_println!("c", 1_048_576, "B");
+        set_color_enabled(false);
+        crate::scaled_println!("d", 1536, "B");
+        crate::scaled_println!("e", 2_345_000u64, "Hz");
+        crate::scaled_println!("f", 1_048_575, "B");
+    });
+    assert_eq!(
+        out,
+        "a: \x1b[36m1023\x1b[0m \x1b[2mB\x1b[0m\n\
+         b: \x1b[36m1.0\x1b[0m \x1b[2mKiB\x1b[0m\n\
+         c: \x1b[36m1.0\x1b[0m \x1b[2mMiB\x1b[0m\n\
+         d: 1.5 KiB\ne: 2.3 MHz\nf: 1.0 MiB\n"
+    );
+}
+
+#[test]
+fn byte_size_and_scaled_round_alike() {
+    let out = capture(|| {
+        set_color_enabled(false);
+        for bytes in [1023, 1024, 1_048_575, 1_048_576, 3 << 30] {
+            crate::println!("{} | {}", ByteSize(bytes), Scaled::new(bytes, "B"));
+        }
+        crate::println!("{}", Scaled::new(999_950, "Hz"));
+    });
+    assert_eq!(
+        out,
+        "1023 B | 1023 B\n\
+         1.00 KiB | 1.0 KiB\n\
+         1.00 MiB | 1.0 MiB\n\
+         1.00 MiB | 1.0 MiB\n\
+         3.00 GiB | 3.0 GiB\n\
+         1.0 MHz\n"
+    );
+}
+
+#[test]
+fn rule_println_fills_width() {
+    let out = capture(|| {
+        crate::rule_println!();
+        crate::rule_println!('=', 5);
+        crate::rule_println!('═', 3, crate::ColorCode::Cyan);
+        crate::rule_println!('一', 5);
+        set_color_enabled(false);
+        crate::rule_println!('*', 2, crate::ColorCode::Cyan);
+    });
+    let expected = format!("{}\n=====\n\x1b[36m═══\x1b[0m\n一一\n**\n", "-".repeat(80));
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn state_diff_println_colors_growth() {
+    struct State {
+        start: usize,
+        end: usize,
+        byte_pos: usize,
+        page_pos: usize,
+        byte_count: usize,
+    }
+
+    let before = State {
+        start: 0x1000,
+        end: 0x9000,
+        byte_pos: 0x1100,
+        page_pos: 0x9000,
+        byte_count: 3,
+    };
+    let after = State {
+        byte_pos: 0x1140,
+        page_pos: 0x7000,
+        byte_count: 1,
+        ..before
+    };
+    let out = capture(|| crate::state_diff_println!(before, after));
+    assert_eq!(
+        out,
+        "start      0x1000 -> 0x1000  +0\n\
+         end        0x9000 -> 0x9000  +0\n\
+         byte_pos   0x1100 -> 0x1140  \x1b[31m+64\x1b[0m\n\
+         page_pos   0x9000 -> 0x7000  \x1b[31m-8192\x1b[0m\n\
+         byte_count 3 -> 1  \x1b[32m-2\x1b[0m\n"
+    );
+}
+
+#[test]
+fn error_chain_indents_sources() {
+    #[derive(Debug)]
+    struct Leaf;
+    #[derive(Debug)]
+    struct Wrapper(Leaf);
+
+    impl core::fmt::Display for Leaf {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.write_str("device timeout")
+        }
+    }
+
+    impl core::fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+            f.write_str("mount failed")
+        }
+    }
+
+    impl core::error::Error for Leaf {}
+
+    impl core::error::Error for Wrapper {
+        fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    let out = capture(|| crate::error_chain!(Wrapper(Leaf)));
+    assert_eq!(
+        out,
+        "\x1b[31mmount failed\x1b[0m\n  caused by: device timeout\n"
+    );
+}
+
+#[test]
+fn matrix_println_aligns_and_highlights() {
+    let m = [[0, 12, 0], [0, 0, 0], [7, 0, 0]];
+    let out = capture(|| crate::matrix_println!(m, crate::ColorCode::Red));
+    assert_eq!(
+        out,
+        "   \x1b[2m0\x1b[0m  \x1b[2m1\x1b[0m  \x1b[2m2\x1b[0m\n\
+         \x1b[2m0\x1b[0m  0 \x1b[31m12\x1b[0m  0\n\
+         \x1b[2m1\x1b[0m  0  0  0\n\
+         \x1b[2m2\x1b[0m  \x1b[31m7\x1b[0m  0  0\n"
+    );
+}
+
+#[test]
+fn matrix_println_pads_ragged_rows() {
+    let rows = std::vec![std::vec![1u8, 2, 3], std::vec![4]];
+    let out = capture(|| {
+        set_color_enabled(false);
+        crate::matrix_println!(rows);
+    });
+    assert_eq!(out, "  0 1 2\n0 1 2 3\n1 4    \n");
+}
+
+#[test]
+fn addr_println_shows_value_and_address() {
+    struct Pair {
+        a: u32,
+        b: u32,
+    }
+
+    let pair = Pair { a: 1, b: 42 };
+    let out = capture(|| crate::addr_println!(pair.b));
+    assert_eq!(out, format!("42 @ {:p}\n", &pair.b));
+    assert!(out.starts_with("42 @ 0x"));
+    assert_ne!(format!("{:p}", &pair.a), format!("{:p}", &pair.b));
+}
+
+#[test]
+fn dump_region_pages_and_colors_rows() {
+    let mut bytes = [0u8; 80];
+    bytes[..5].copy_from_slice(b"Hello");
+    bytes[70] = 0xff;
+    let out = capture(|| {
+        set_dump_pause(DumpPause::Delay(core::time::Duration::ZERO));
+        dump_region(&bytes[..48], 2);
+    });
+    assert_eq!(
+        out,
+        "\x1b[36m00000000\x1b[0m  48 65 6c 6c 6f 00 00 00 00 00 00 00 00 00 00 00  |Hello...........|\n\
+         \x1b[2m00000010  00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00  |................|\x1b[0m\n\
+         \x1b[2m00000020  00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00  |................|\x1b[0m\n"
+    );
+
+    // A page of one row, then the prompt before the next page
+    let out = capture(|| {
+        set_color_enabled(false);
+        set_dump_pause(DumpPause::Key);
+        dump_region(&bytes[48..], 1);
+    });
+    assert_eq!(
+        out,
+        "00000000  00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00  |................|\n\
+         -- more --\n\
+         00000010  00 00 00 00 00 00 ff 00 00 00 00 00 00 00 00 00  |................|\n"
+    );
+}
+
+#[test]
+fn iprintln_indents_by_level() {
+    let out = capture(|| {
+        crate::iprintln!("boot");
+        indent();
+        crate::iprintln!("mm");
+        indent();
+        crate::iprintln!("heap {}", 42);
+        dedent();
+        crate::iprintln!("fs");
+        dedent();
+        dedent();
+        crate::iprintln!("done");
+        set_indent_unit("| ");
+        indent();
+        indent();
+        crate::iprintln!("x");
+    });
+    assert_eq!(out, "boot\n  mm\n    heap 42\n  fs\ndone\n| | x\n");
+    assert_eq!(indent_level(), 2);
+}
+
+#[test]
+fn json_println_quotes_strings_only() {
+    let out = capture(|| {
+        crate::json_println!("cpus" => 4, "model" => "riscv64");
+        crate::json_println!("ok" => true, "load" => 0.5, "temp" => -3i8, "nan" => f64::NAN);
+        crate::json_println!("name" => "say \"hi\"\\\n", "c" => 'x');
+        crate::json_println!();
+    });
+    assert_eq!(
+        out,
+        "{\"cpus\":4,\"model\":\"riscv64\"}\n\
+         {\"ok\":true,\"load\":0.5,\"temp\":-3,\"nan\":null}\n\
+         {\"name\":\"say \\\"hi\\\"\\\\\\n\",\"c\":\"x\"}\n\
+         {}\n"
+    );
+}
+
+#[test]
+fn wrap_println_breaks_on_spaces() {
+    let out = capture(|| {
+        set_terminal_width(20);
+        crate::wrap_println!("the quick brown fox jumps over");
+        crate::wrap_println!("the quick brown fox jumps over the lazy dog and {}", "cat");
+        crate::wrap_println!(indent: 2, "mounted {} on ramfs ok", "/dev/root");
+    });
+    assert_eq!(
+        out,
+        "the quick brown fox\njumps over\n\
+         the quick brown fox\njumps over the lazy\ndog and cat\n\
+         mounted /dev/root on\n  ramfs ok\n"
+    );
+}
+
+#[test]
+fn wrap_println_splits_long_words() {
+    let out = capture(|| {
+        set_terminal_width(8);
+        crate::wrap_println!("a 0123456789abcdef z");
+        crate::wrap_println!(indent: 2, "{}", "x".repeat(14));
+    });
+    assert_eq!(out, "a\n01234567\n89abcdef\nz\nxxxxxxxx\n  xxxxxx\n");
+}
+
+#[test]
+fn wrap_println_ignores_escapes() {
+    let out = capture(|| {
+        set_terminal_width(10);
+        crate::wrap_println!("{} world", Colored::new(crate::ColorCode::Red, "hello"));
+        crate::wrap_println!("内核 启动 完成");
+    });
+    assert_eq!(out, "\x1b[31mhello\x1b[0m\nworld\n内核 启动\n完成\n");
+}
+
+#[test]
+fn network_addresses() {
+    let out = capture(|| {
+        crate::ipv4_println!("ip", [10, 0, 2, 15]);
+        crate::ipv4_println!("gateway", [192, 168, 0, 1]);
+        crate::mac_println!("mac", [0x52, 0x54, 0x00, 0x0a, 0xbc, 0x5]);
+    });
+    assert_eq!(
+        out,
+        "ip: 10.0.2.15\ngateway: 192.168.0.1\nmac: 52:54:00:0a:bc:05\n"
+    );
+}
@@ -0,0 +1,119 @@
+//! Printing of hierarchical data as trees.
+
+use core::fmt;
+
+use super::{color_enabled, Colored};
+use crate::ColorCode;
+
+/// A node of a caller-built tree, see [`print_tree`].
+pub struct TreeNode<'a> {
+    /// The text shown for the node.
+    pub label: &'a str,
+    /// The color of the label, if any.
+    pub color: Option<ColorCode>,
+    /// The child nodes, in display order.
+    pub children: &'a [TreeNode<'a>],
+}
+
+impl<'a> TreeNode<'a> {
+    /// Creates a node with an uncolored label.
+    pub const fn new(label: &'a str, children: &'a [TreeNode<'a>]) -> Self {
+        Self {
+            label,
+            color: None,
+            children,
+        }
+    }
+
+    /// Creates a node whose label is shown in `color`.
+    pub const fn colored(label: &'a str, color: ColorCode, children: &'a [TreeNode<'a>]) -> Self {
+        Self {
+            label,
+            color: Some(color),
+            children,
+        }
+    }
+}
+
+/// The branches of the ancestors of a node, innermost last.
+struct Prefix<'p> {
+    parent: Option<&'p Prefix<'p>>,
+    /// Whether the ancestor was the last of its siblings.
+    last: bool,
+}
+
+/// Displays a tree with `├──`/`└──`/`│` connectors, or `|--`/`` `-- ``/`|`
+/// if colors are disabled by [`set_color_enabled`].
+///
+/// [`set_color_enabled`]: super::set_color_enabled
+pub struct Tree<'a> {
+    root: &'a TreeNode<'a>,
+}
+
+impl<'a> Tree<'a> {
+    /// Creates a new [`Tree`] rooted at `root`.
+    pub fn new(root: &'a TreeNode<'a>) -> Self {
+        Self { root }
+    }
+
+    fn write_prefix(
+        f: &mut fmt::Formatter<'_>,
+        prefix: Option<&Prefix<'_>>,
+        unicode: bool,
+    ) -> fmt::Result {
+        if let Some(p) = prefix {
+            Self::write_prefix(f, p.parent, unicode)?;
+            f.write_str(match (p.last, unicode) {
+                (true, _) => "    ",
+                (false, true) => "│   ",
+                (false, false) => "|   ",
+            })?;
+        }
+        Ok(())
+    }
+
+    fn write_label(f: &mut fmt::Formatter<'_>, node: &TreeNode<'_>) -> fmt::Result {
+        match node.color {
+            Some(color) => writeln!(f, "{}", Colored::new(color, node.label)),
+            None => writeln!(f, "{}", node.label),
+        }
+    }
+
+    fn write_children(
+        f: &mut fmt::Formatter<'_>,
+        node: &TreeNode<'_>,
+        prefix: Option<&Prefix<'_>>,
+        unicode: bool,
+    ) -> fmt::Result {
+        for (i, child) in node.children.iter().enumerate() {
+            let last = i + 1 == node.children.len();
+            Self::write_prefix(f, prefix, unicode)?;
+            f.write_str(match (last, unicode) {
+                (true, true) => "└── ",
+                (false, true) => "├── ",
+                (true, false) => "`-- ",
+                (false, false) => "|-- ",
+            })?;
+            Self::write_label(f, child)?;
+            let child_prefix = Prefix {
+                parent: prefix,
+                last,
+            };
+            Self::write_children(f, child, Some(&child_prefix), unicode)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Tree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unicode = color_enabled();
+        Self::write_label(f, self.root)?;
+        Self::write_children(f, self.root, None, unicode)
+    }
+}
+
+/// Prints the tree rooted at `root`, see [`Tree`].
+pub fn print_tree(root: &TreeNode<'_>) {
+    crate::print!("{}", Tree::new(root));
+}
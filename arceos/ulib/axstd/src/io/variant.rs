@@ -0,0 +1,42 @@
+//! Printing enum variants by name.
+
+use crate::ColorCode;
+
+/// Enums that can report the name and discriminant of their variants.
+///
+/// `no_std` has no reflection, so this is implemented by hand, with
+/// [`ColorCode`] as the reference implementation.
+pub trait VariantName {
+    /// Returns the name of the variant, e.g. `"Red"`.
+    fn variant_name(&self) -> &'static str;
+
+    /// Returns the discriminant of the variant, e.g. `31` for `Red`.
+    fn discriminant(&self) -> u8;
+}
+
+impl VariantName for ColorCode {
+    fn variant_name(&self) -> &'static str {
+        match self {
+            Self::Black => "Black",
+            Self::Red => "Red",
+            Self::Green => "Green",
+            Self::Yellow => "Yellow",
+            Self::Blue => "Blue",
+            Self::Magenta => "Magenta",
+            Self::Cyan => "Cyan",
+            Self::White => "White",
+            Self::BrightBlack => "BrightBlack",
+            Self::BrightRed => "BrightRed",
+            Self::BrightGreen => "BrightGreen",
+            Self::BrightYellow => "BrightYellow",
+            Self::BrightBlue => "BrightBlue",
+            Self::BrightMagenta => "BrightMagenta",
+            Self::BrightCyan => "BrightCyan",
+            Self::BrightWhite => "BrightWhite",
+        }
+    }
+
+    fn discriminant(&self) -> u8 {
+        *self as u8
+    }
+}
@@ -0,0 +1,254 @@
+//! Display width measurement shared by the formatting helpers.
+
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::SliceWriter;
+
+/// Messages longer than this are printed by `center_println!` unpadded.
+const CENTER_BUF_LEN: usize = 256;
+
+/// Messages longer than this are printed by `wrap_println!` unwrapped.
+const WRAP_BUF_LEN: usize = 1024;
+
+static TERMINAL_WIDTH: AtomicUsize = AtomicUsize::new(80);
+
+/// Sets the console width in columns that [`wrap_println!`] wraps at.
+/// Defaults to 80; zero is treated as one.
+///
+/// [`wrap_println!`]: crate::wrap_println
+pub fn set_terminal_width(width: usize) {
+    TERMINAL_WIDTH.store(width.max(1), Ordering::Relaxed);
+}
+
+/// Returns the console width set with [`set_terminal_width`].
+pub fn terminal_width() -> usize {
+    TERMINAL_WIDTH.load(Ordering::Relaxed)
+}
+
+/// Returns the length in bytes of the ANSI escape sequence at the start of
+/// `s`, or `None` if `s` does not start with one.
+///
+/// Recognizes CSI sequences (`ESC [ ... final`), OSC sequences (`ESC ] ...`
+/// terminated by BEL or `ESC \`) and two-byte `ESC x` sequences.
+fn escape_len(s: &str) -> Option<usize> {
+    let bytes = s.as_bytes();
+    if bytes.first() != Some(&0x1b) {
+        return None;
+    }
+    let len = match bytes.get(1) {
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        Some(b']') => {
+            let mut i = 2;
+            loop {
+                match bytes.get(i) {
+                    None => break bytes.len(),
+                    Some(0x07) => break i + 1,
+                    Some(0x1b) if bytes.get(i + 1) == Some(&b'\\') => break i + 2,
+                    _ => i += 1,
+                }
+            }
+        }
+        Some(b) if b.is_ascii() => 2,
+        _ => 1,
+    };
+    Some(len)
+}
+
+/// Iterates over the visible characters of `s`, skipping escape sequences,
+/// together with their byte offsets.
+fn visible_chars(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut pos = 0;
+    core::iter::from_fn(move || {
+        while let Some(len) = escape_len(&s[pos..]) {
+            pos += len;
+        }
+        let c = s[pos..].chars().next()?;
+        let item = (pos, c);
+        pos += c.len_utf8();
+        Some(item)
+    })
+}
+
+/// Returns the number of columns `c` occupies: two for East Asian wide and
+/// fullwidth characters (CJK ideographs, Hangul, kana, fullwidth forms and
+/// the common emoji blocks), one otherwise.
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// Returns the number of columns `s` occupies on the console.
+///
+/// Wide characters such as CJK ideographs count as two columns, every other
+/// Unicode scalar value as one, and ANSI escape sequences as none.
+pub fn display_width(s: &str) -> usize {
+    visible_chars(s).map(|(_, c)| char_width(c)).sum()
+}
+
+/// Returns the longest prefix of `s` that fits in `width` columns, see
+/// [`display_width`].
+///
+/// Escape sequences directly following the last visible character are kept,
+/// so that a trailing color reset is not lost. A wide character that would
+/// straddle the limit is left out.
+pub fn truncate_str(s: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (pos, c) in visible_chars(s) {
+        used += char_width(c);
+        if used > width {
+            return &s[..pos];
+        }
+    }
+    s
+}
+
+/// Copies the longest prefix of `s` that fits in `width` columns into `out`,
+/// returning the number of bytes written.
+///
+/// A multi-byte character is never split, even if `out` is too small to hold
+/// the whole prefix.
+pub fn truncate_to_width(s: &str, width: usize, out: &mut [u8]) -> usize {
+    let prefix = truncate_str(s, width);
+    let mut len = prefix.len().min(out.len());
+    while !prefix.is_char_boundary(len) {
+        len -= 1;
+    }
+    out[..len].copy_from_slice(&prefix.as_bytes()[..len]);
+    len
+}
+
+/// Returns the number of columns `args` occupies once formatted, see
+/// [`display_width`].
+pub(super) fn args_width(args: fmt::Arguments) -> usize {
+    struct Counter(usize);
+
+    impl fmt::Write for Counter {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0 += display_width(s);
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    let _ = fmt::write(&mut counter, args);
+    counter.0
+}
+
+#[doc(hidden)]
+pub fn __center_println(width: usize, args: fmt::Arguments) {
+    let mut buf = [0u8; CENTER_BUF_LEN];
+    let mut w = SliceWriter::new(&mut buf);
+    if fmt::write(&mut w, args).is_err() {
+        // Too long to measure, so certainly wider than any sane `width`.
+        crate::println!("{}", args);
+        return;
+    }
+    let len = w.written();
+    // Only whole strings were written, so the buffer is valid UTF-8.
+    let msg = core::str::from_utf8(&buf[..len]).unwrap_or_default();
+    let pad = width.saturating_sub(display_width(msg));
+    let left = pad / 2;
+    crate::println!(
+        "{:left$}{}{:right$}",
+        "",
+        msg,
+        "",
+        left = left,
+        right = pad - left
+    );
+}
+
+#[doc(hidden)]
+pub fn __rule_println(ch: char, width: usize, color: Option<crate::ColorCode>) {
+    struct Rule(char, usize);
+
+    impl fmt::Display for Rule {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for _ in 0..self.1 {
+                fmt::Write::write_char(f, self.0)?;
+            }
+            Ok(())
+        }
+    }
+
+    // Wide characters take two columns each; never overshoot `width`.
+    let count = width / char_width(ch).max(1);
+    match color {
+        Some(color) => {
+            crate::println!("{}", super::Colored::new(color, Rule(ch, count)));
+        }
+        None => {
+            crate::println!("{}", Rule(ch, count));
+        }
+    }
+}
+
+/// Splits the first line of at most `width` columns off `s`, breaking after
+/// the last space that fits, or inside a word that alone is too wide.
+fn split_line(s: &str, width: usize) -> (&str, &str) {
+    if display_width(s) <= width {
+        return (s, "");
+    }
+    let prefix = truncate_str(s, width);
+    let (line, rest) = if prefix.ends_with(' ') || s[prefix.len()..].starts_with(' ') {
+        (prefix, &s[prefix.len()..])
+    } else {
+        match prefix.trim_end_matches(' ').rfind(' ') {
+            Some(i) => (&prefix[..i], &s[i..]),
+            // Always make progress, even on a character wider than `width`.
+            None if prefix.is_empty() => s.split_at(s.chars().next().map_or(0, char::len_utf8)),
+            None => (prefix, &s[prefix.len()..]),
+        }
+    };
+    (line.trim_end_matches(' '), rest.trim_start_matches(' '))
+}
+
+#[doc(hidden)]
+pub fn __wrap_println(indent: usize, args: fmt::Arguments) {
+    let mut buf = [0u8; WRAP_BUF_LEN];
+    let mut w = SliceWriter::new(&mut buf);
+    if fmt::write(&mut w, args).is_err() {
+        crate::println!("{}", args);
+        return;
+    }
+    let len = w.written();
+    // Only whole strings were written, so the buffer is valid UTF-8.
+    let msg = core::str::from_utf8(&buf[..len]).unwrap_or_default();
+
+    let width = terminal_width();
+    // Continuation lines keep at least one column for text.
+    let indent = indent.min(width - 1);
+    let mut first = true;
+    for paragraph in msg.split('\n') {
+        let mut rest = paragraph;
+        loop {
+            let pad = if first { 0 } else { indent };
+            let (line, tail) = split_line(rest, width - pad);
+            crate::println!("{:pad$}{}", "", line, pad = pad);
+            first = false;
+            rest = tail;
+            if rest.is_empty() {
+                break;
+            }
+        }
+    }
+}
@@ -78,6 +78,7 @@ pub mod time;
 
 #[repr(u8)]
 #[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ColorCode {
     Black = 30,
     Red = 31,
@@ -22,9 +22,885 @@ macro_rules! println {
     }
 }
 
+/// Prints to the error sink.
+///
+/// The kernel console has no separate error channel, so this shares it with
+/// [`print!`], but it is not throttled by
+/// [`set_print_budget`](crate::io::set_print_budget).
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {
+        $crate::io::__eprint_impl(format_args!($($arg)*));
+    }
+}
+
+/// Prints to the error sink, with a newline.
+#[macro_export]
+macro_rules! eprintln {
+    () => { $crate::eprint!("\n") };
+    ($($arg:tt)*) => {
+        $crate::io::__eprint_impl(format_args!("{}\n", format_args!($($arg)*)));
+    }
+}
+
+/// Prints to the standard output, with a newline, only in builds with
+/// `debug_assertions` enabled.
+///
+/// In release builds the arguments are not evaluated, though they are still
+/// type-checked, like [`debug_assert!`].
+#[macro_export]
+macro_rules! debug_println {
+    ($($arg:tt)*) => {{
+        if cfg!(debug_assertions) {
+            $crate::println!($($arg)*);
+        }
+    }};
+}
+
+/// Prints to the standard output in the given [`ColorCode`], with a newline.
+///
+/// The color is omitted if disabled by [`set_color_enabled`].
+///
+/// [`ColorCode`]: crate::ColorCode
+/// [`set_color_enabled`]: crate::io::set_color_enabled
 #[macro_export]
 macro_rules! color_println {
     ($color:expr, $($arg:tt)*) => {{
-        println!("\x1b[{}m{}\x1b[0m", $color as u8, format_args!($($arg)*));
+        $crate::println!("{}", $crate::io::Colored::new($color, format_args!($($arg)*)));
+    }};
+}
+
+/// Prints a message colored by where `value` falls between two thresholds:
+/// green below `warn`, yellow from `warn` up to `crit`, and red at or above
+/// `crit`.
+#[macro_export]
+macro_rules! threshold_println {
+    ($value:expr, $warn:expr, $crit:expr, $($arg:tt)*) => {{
+        let value = $value;
+        let color = if value >= $crit {
+            $crate::ColorCode::Red
+        } else if value >= $warn {
+            $crate::ColorCode::Yellow
+        } else {
+            $crate::ColorCode::Green
+        };
+        $crate::color_println!(color, $($arg)*);
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_println {
+    ($level:ident, $($arg:tt)+) => {{
+        let level = $crate::io::LogLevel::$level;
+        if $crate::io::__log_enabled(level) {
+            $crate::println!(
+                "{}{}",
+                $crate::io::__log_prefix(level),
+                $crate::io::Colored::new(
+                    level.color(),
+                    format_args!("[{}] {}", level.tag(), format_args!($($arg)+))
+                )
+            );
+        }
+    }};
+}
+
+/// Prints to the standard output in a composed [`StyleBuilder`] style, with
+/// a newline.
+///
+/// ```ignore
+/// let alert = StyleBuilder::new().fg(ColorCode::Red).bold().underline();
+/// style_println!(alert, "{} errors", n);
+/// ```
+///
+/// [`StyleBuilder`]: crate::io::StyleBuilder
+#[macro_export]
+macro_rules! style_println {
+    ($style:expr, $($arg:tt)*) => {{
+        $crate::println!("{}", $style.paint(format_args!($($arg)*)));
+    }};
+}
+
+/// Prints an error message in red, tagged with `[ERROR]`, with a newline.
+///
+/// It is preceded by a bell if enabled by
+/// [`set_bell_on_error`](crate::io::set_bell_on_error).
+///
+/// Like the other logging macros, it prints nothing if its level is above
+/// the one set by [`set_log_level`](crate::io::set_log_level).
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)+) => {
+        $crate::__log_println!(Error, $($arg)+)
+    };
+}
+
+/// Prints a warning message in yellow, tagged with `[WARN]`, with a newline.
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)+) => {
+        $crate::__log_println!(Warn, $($arg)+)
+    };
+}
+
+/// Prints an informational message in green, tagged with `[INFO]`, with a
+/// newline.
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => {
+        $crate::__log_println!(Info, $($arg)+)
+    };
+}
+
+/// Prints a debug message in cyan, tagged with `[DEBUG]`, with a newline.
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        $crate::__log_println!(Debug, $($arg)+)
+    };
+}
+
+/// Prints a trace message in bright black, tagged with `[TRACE]`, with a
+/// newline.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        $crate::__log_println!(Trace, $($arg)+)
+    };
+}
+
+/// Prints a machine-parseable log line in logfmt style, with a newline,
+/// e.g. `level=info msg=started port=8080`.
+///
+/// `$level` is a [`LogLevel`](crate::io::LogLevel) and is filtered like the
+/// other log macros. Keys and values can be any `Display`; a value is quoted
+/// when it is empty or contains a space, `=` or `"`:
+///
+/// ```ignore
+/// logfmt!(LogLevel::Info, "msg" => "net up", "port" => 8080);
+/// ```
+#[macro_export]
+macro_rules! logfmt {
+    ($level:expr $(, $key:expr => $value:expr)* $(,)?) => {
+        $crate::io::__logfmt(
+            $level,
+            &[$((
+                &$key as &dyn ::core::fmt::Display,
+                &$value as &dyn ::core::fmt::Display,
+            )),*],
+        )
+    };
+}
+
+/// Evaluates a [`Result`], printing the error with [`error!`] if it is an
+/// `Err`, and yields the original [`Result`] unchanged.
+///
+/// The `Ok` path prints nothing, so the result can still be propagated with
+/// `?`:
+///
+/// ```ignore
+/// let file = log_err!(File::open("/etc/hosts"))?;
+/// ```
+///
+/// Given a [`LogLevel`](crate::io::LogLevel) and a message instead, prints
+/// the message with a colored `[LEVEL]` tag, with a newline. Errors and
+/// warnings go to the error sink (see [`eprint!`]), the other levels to the
+/// standard output:
+///
+/// ```ignore
+/// log_err!(LogLevel::Warn, "retrying {}", dev);
+/// ```
+///
+/// Both forms honor [`set_log_level`](crate::io::set_log_level) and
+/// [`set_color_enabled`](crate::io::set_color_enabled).
+#[macro_export]
+macro_rules! log_err {
+    ($result:expr) => {{
+        let result = $result;
+        if let Err(ref err) = result {
+            $crate::error!("{}: {:?}", stringify!($result), err);
+        }
+        result
+    }};
+    ($level:expr, $($arg:tt)+) => {
+        $crate::io::__log_err($level, format_args!($($arg)+))
+    };
+}
+
+/// Prints an unsigned integer in binary with its bits grouped, followed by a
+/// newline.
+///
+/// All bits up to the type's width are shown, most significant first, with a
+/// `_` between every `group` bits (e.g. `1010_1100`).
+#[macro_export]
+macro_rules! bin_println {
+    ($value:expr, $group:expr) => {{
+        $crate::println!("{}", $crate::io::GroupedBinary::new($value, $group));
+    }};
+}
+
+/// Prints `label: 0x...` with the value in hex, zero-padded to the width of
+/// its type, see [`PaddedHex`](crate::io::PaddedHex).
+///
+/// The digits are cyan unless a [`ColorCode`](crate::ColorCode) is given as
+/// the third argument.
+#[macro_export]
+macro_rules! hex_println {
+    ($label:expr, $value:expr) => {
+        $crate::hex_println!($label, $value, $crate::ColorCode::Cyan)
+    };
+    ($label:expr, $value:expr, $color:expr) => {{
+        $crate::println!(
+            "{}: {}",
+            $label,
+            $crate::io::PaddedHex::with_color($value, $color)
+        );
+    }};
+}
+
+/// Prints `label: a.b.c.d` for an IPv4 address given as `[u8; 4]` in
+/// network order.
+#[macro_export]
+macro_rules! ipv4_println {
+    ($label:expr, $bytes:expr) => {{
+        let bytes: [u8; 4] = $bytes;
+        $crate::println!("{}: {}", $label, ::core::net::Ipv4Addr::from(bytes));
+    }};
+}
+
+/// Prints `label: aa:bb:cc:dd:ee:ff` for a MAC address given as `[u8; 6]`,
+/// see [`MacAddr`](crate::io::MacAddr).
+#[macro_export]
+macro_rules! mac_println {
+    ($label:expr, $bytes:expr) => {{
+        $crate::println!("{}: {}", $label, $crate::io::MacAddr($bytes));
+    }};
+}
+
+/// Prints an enum value as `Name(discriminant)`, e.g. `Red(31)`.
+///
+/// The value's type must implement [`VariantName`](crate::io::VariantName).
+#[macro_export]
+macro_rules! enum_println {
+    ($value:expr) => {{
+        let value = &$value;
+        $crate::println!(
+            "{}({})",
+            $crate::io::VariantName::variant_name(value),
+            $crate::io::VariantName::discriminant(value)
+        );
+    }};
+}
+
+/// Prints a message inside a box drawn in the given [`ColorCode`].
+///
+/// The box is sized to the widest line, and each line of a multi-line
+/// message gets its own side borders. Lines wider than 76 columns are
+/// truncated. When escape sequences are disabled by [`set_color_enabled`],
+/// the box is drawn with ASCII `+`, `-` and `|` instead of Unicode.
+///
+/// [`ColorCode`]: crate::ColorCode
+/// [`set_color_enabled`]: crate::io::set_color_enabled
+#[macro_export]
+macro_rules! box_println {
+    ($color:expr, $($arg:tt)*) => {
+        $crate::io::__box_println($color, format_args!($($arg)*))
+    };
+}
+
+/// Prints a message centered within `width` columns, padded with spaces on
+/// both sides, with a newline.
+///
+/// The width is measured with [`display_width`](crate::io::display_width),
+/// so escape sequences and wide characters are accounted for. When the
+/// padding is odd, the extra space goes to the right. Messages wider than
+/// `width` (or longer than 256 bytes) are printed unchanged.
+#[macro_export]
+macro_rules! center_println {
+    ($width:expr, $($arg:tt)*) => {
+        $crate::io::__center_println($width, format_args!($($arg)*))
+    };
+}
+
+/// Formats a colored message into a caller-provided `&mut [u8]` instead of
+/// printing it.
+///
+/// Evaluates to `Ok(n)` with the number of bytes written, or `Err(fmt::Error)`
+/// if the output did not fit (the buffer then holds the truncated prefix).
+/// Honors [`set_color_enabled`](crate::io::set_color_enabled).
+#[macro_export]
+macro_rules! color_format {
+    ($buf:expr, $color:expr, $($arg:tt)*) => {
+        $crate::io::__color_format($buf, $color, format_args!($($arg)*))
+    };
+}
+
+/// Prints `label: <size>` with the byte count in binary units, see
+/// [`ByteSize`](crate::io::ByteSize).
+#[macro_export]
+macro_rules! size_println {
+    ($label:expr, $bytes:expr) => {{
+        $crate::println!("{}: {}", $label, $crate::io::ByteSize($bytes as u64));
+    }};
+}
+
+/// Prints `label: <value>` with the count scaled to a readable prefix and one
+/// decimal, e.g. `heap: 1.5 KiB`, see [`Scaled`](crate::io::Scaled).
+///
+/// A unit of `"B"` uses binary prefixes, other units SI ones.
+#[macro_export]
+macro_rules! scaled_println {
+    ($label:expr, $value:expr, $unit:expr) => {{
+        $crate::println!(
+            "{}: {}",
+            $label,
+            $crate::io::Scaled::new($value as u64, $unit)
+        );
+    }};
+}
+
+/// Prints an error in red, followed by each error in its
+/// [`source`](core::error::Error::source) chain on its own line, indented one
+/// step further per level:
+///
+/// ```text
+/// mount failed
+///   caused by: read error at block 7
+///     caused by: device timeout
+/// ```
+#[macro_export]
+macro_rules! error_chain {
+    ($err:expr) => {
+        $crate::io::__error_chain(&$err)
+    };
+}
+
+/// Prints a name in bold followed by one indented `field = value` line per
+/// field, with the field names colored.
+///
+/// Values are printed with `{:?}` unless a format string is given after the
+/// field name:
+///
+/// ```ignore
+/// dump_fields!("TrapFrame", sepc: "{:#x}" = tf.sepc, sstatus: "{:#x}" = tf.sstatus, a0 = tf.a0);
+/// ```
+///
+/// The colors are omitted if disabled by [`set_color_enabled`].
+///
+/// [`set_color_enabled`]: crate::io::set_color_enabled
+#[macro_export]
+macro_rules! dump_fields {
+    (@value $value:expr) => {
+        format_args!("{:?}", $value)
+    };
+    (@value $value:expr, $fmt:literal) => {
+        format_args!($fmt, $value)
+    };
+    ($name:expr $(, $field:ident $(: $fmt:literal)? = $value:expr)* $(,)?) => {{
+        $crate::println!("{}", $crate::io::Colored::bold($name));
+        $(
+            $crate::println!(
+                "  {} = {}",
+                $crate::io::Colored::new($crate::ColorCode::Cyan, stringify!($field)),
+                $crate::dump_fields!(@value $value $(, $fmt)?),
+            );
+        )*
+    }};
+}
+
+/// Prints a table of allocator usage snapshots, one row per recorded phase.
+///
+/// `$recorder` is anything whose `iter()` yields `(label, stats)` pairs where
+/// `stats` has `used_bytes` and `used_pages` fields, such as
+/// `bump_allocator::HistoryRecorder`.
+#[macro_export]
+macro_rules! history_println {
+    ($recorder:expr) => {{
+        $crate::println!("{:<16} {:>12} {:>10}", "label", "used_bytes", "used_pages");
+        for (label, stats) in $recorder.iter() {
+            $crate::println!(
+                "{:<16} {:>12} {:>10}",
+                label,
+                stats.used_bytes,
+                stats.used_pages
+            );
+        }
+    }};
+}
+
+/// Prints a clickable OSC 8 hyperlink to `url` showing the formatted text,
+/// with a newline.
+///
+/// Falls back to `text (url)` if escape sequences are disabled by
+/// [`set_color_enabled`].
+///
+/// [`set_color_enabled`]: crate::io::set_color_enabled
+#[macro_export]
+macro_rules! link_println {
+    ($url:expr, $($arg:tt)+) => {{
+        $crate::println!("{}", $crate::io::Hyperlink::new($url, format_args!($($arg)+)));
+    }};
+}
+
+/// Checks a condition without panicking.
+///
+/// Unlike [`assert!`], a failed check only prints a red warning with the
+/// condition text, the location and the optional message, and counts the
+/// failure in [`soft_assert_failures`]. Evaluates to whether the condition
+/// held.
+///
+/// [`soft_assert_failures`]: crate::io::soft_assert_failures
+#[macro_export]
+macro_rules! soft_assert {
+    ($cond:expr $(,)?) => {{
+        let ok: bool = $cond;
+        if !ok {
+            $crate::io::__soft_assert_failed(stringify!($cond), file!(), line!(), None);
+        }
+        ok
+    }};
+    ($cond:expr, $($arg:tt)+) => {{
+        let ok: bool = $cond;
+        if !ok {
+            $crate::io::__soft_assert_failed(
+                stringify!($cond),
+                file!(),
+                line!(),
+                Some(format_args!($($arg)+)),
+            );
+        }
+        ok
     }};
 }
+
+/// Checks a boot precondition without panicking.
+///
+/// If `$cond` is false, prints the message in the given [`ColorCode`] so that
+/// boot can continue in a degraded mode. `$cond` is evaluated once, and the
+/// macro evaluates to whether it held:
+///
+/// ```ignore
+/// if !require!(virtio_blk.is_some(), ColorCode::Yellow, "no block device, skipping fs") {
+///     return;
+/// }
+/// ```
+///
+/// [`ColorCode`]: crate::ColorCode
+#[macro_export]
+macro_rules! require {
+    ($cond:expr, $color:expr, $($arg:tt)+) => {{
+        let ok: bool = $cond;
+        if !ok {
+            $crate::color_println!($color, $($arg)+);
+        }
+        ok
+    }};
+}
+
+/// Prints a message only if `value` differs from the one seen last time at
+/// this call site, e.g. for periodic progress or temperature readouts.
+///
+/// `key` names the static [`OnChange`] tracker declared for the call site, so
+/// it should be an upper-case identifier. The first call always prints.
+///
+/// ```ignore
+/// println_on_change!(FREE_PAGES, free, "free pages: {}", free);
+/// ```
+///
+/// [`OnChange`]: crate::io::OnChange
+#[macro_export]
+macro_rules! println_on_change {
+    ($key:ident, $value:expr, $($arg:tt)*) => {{
+        static $key: $crate::io::OnChange = $crate::io::OnChange::new();
+        if $key.update($value) {
+            $crate::println!($($arg)*);
+        }
+    }};
+}
+
+/// Prints a message the first time this call site is reached, and nothing on
+/// later calls, e.g. for warnings inside loops or hot paths.
+///
+/// The arguments are not evaluated on later calls.
+#[macro_export]
+macro_rules! println_once {
+    ($($arg:tt)*) => {{
+        static ONCE: $crate::io::Once = $crate::io::Once::new();
+        if ONCE.first() {
+            $crate::println!($($arg)*);
+        }
+    }};
+}
+
+/// Warns in yellow that a deprecated code path was hit, naming the call site
+/// and the suggested replacement, e.g.
+/// `[DEPRECATED] src/mm.rs:42: use map_region instead`.
+///
+/// Each call site warns only once, see [`println_once!`]. The color is
+/// omitted if disabled by [`set_color_enabled`](crate::io::set_color_enabled).
+///
+/// ```ignore
+/// deprecated!("use map_region instead");
+/// deprecated!("use {} instead", new_name);
+/// ```
+#[macro_export]
+macro_rules! deprecated {
+    ($($arg:tt)+) => {
+        $crate::println_once!(
+            "{}",
+            $crate::io::Colored::new(
+                $crate::ColorCode::Yellow,
+                format_args!(
+                    "[DEPRECATED] {}:{}: {}",
+                    file!(),
+                    line!(),
+                    format_args!($($arg)+)
+                )
+            )
+        )
+    };
+}
+
+/// Prints a label followed by an inline usage bar for a permille value, with
+/// a newline, e.g. `heap [██████····] 62.3%`.
+///
+/// See [`Gauge`](crate::io::Gauge) for the color bands and clamping.
+#[macro_export]
+macro_rules! gauge_println {
+    ($label:expr, $permille:expr) => {{
+        $crate::println!("{} {}", $label, $crate::io::Gauge::new($permille));
+    }};
+}
+
+/// Prints a `[n/total]` step marker followed by the message, with a newline.
+///
+/// The marker is colored by completion, see [`StepMarker`]. The color is
+/// omitted if disabled by [`set_color_enabled`].
+///
+/// [`StepMarker`]: crate::io::StepMarker
+/// [`set_color_enabled`]: crate::io::set_color_enabled
+#[macro_export]
+macro_rules! step_println {
+    ($n:expr, $total:expr, $($arg:tt)*) => {{
+        $crate::println!(
+            "{} {}",
+            $crate::io::StepMarker::new($n, $total),
+            format_args!($($arg)*)
+        );
+    }};
+}
+
+/// Prints how an early allocator's state changed between two snapshots, one
+/// field per line with the old and new values and the delta.
+///
+/// `$before` and `$after` are anything with `start`, `end`, `byte_pos`,
+/// `page_pos` and `byte_count` fields, such as the
+/// `bump_allocator::EarlyAllocatorState` returned by `export_state`. Deltas
+/// that leave less memory free (a growing `byte_pos`, a shrinking `page_pos`
+/// or arena) are red and those that free memory are green, unless colors are
+/// disabled by [`set_color_enabled`](crate::io::set_color_enabled).
+#[macro_export]
+macro_rules! state_diff_println {
+    (@field $name:ident, $fmt:literal, $grows_down:expr, $before:expr, $after:expr) => {
+        $crate::io::__print_field_diff(
+            stringify!($name),
+            format_args!(concat!($fmt, " -> ", $fmt), $before.$name, $after.$name),
+            ($after.$name as isize).wrapping_sub($before.$name as isize),
+            $grows_down,
+        )
+    };
+    ($before:expr, $after:expr $(,)?) => {{
+        let (before, after) = (&$before, &$after);
+        $crate::state_diff_println!(@field start, "{:#x}", false, before, after);
+        $crate::state_diff_println!(@field end, "{:#x}", true, before, after);
+        $crate::state_diff_println!(@field byte_pos, "{:#x}", false, before, after);
+        $crate::state_diff_println!(@field page_pos, "{:#x}", true, before, after);
+        $crate::state_diff_println!(@field byte_count, "{}", false, before, after);
+    }};
+}
+
+/// Prints two byte buffers side by side as hex, one line per 16 bytes, with
+/// differing bytes highlighted in red.
+///
+/// The tail of the longer buffer counts as differing, see [`BufferDiff`].
+///
+/// [`BufferDiff`]: crate::io::BufferDiff
+#[macro_export]
+macro_rules! diff_println {
+    ($a:expr, $b:expr $(,)?) => {{
+        $crate::print!("{}", $crate::io::BufferDiff::new(&$a[..], &$b[..]));
+    }};
+}
+
+/// Prints a count followed by the singular noun when it is `1` and the
+/// plural noun otherwise, with a newline.
+///
+/// If only the singular noun is given, the plural is formed by appending `s`.
+///
+/// ```ignore
+/// count_println!(1, "file");                // 1 file
+/// count_println!(0, "entry", "entries");    // 0 entries
+/// ```
+#[macro_export]
+macro_rules! count_println {
+    ($n:expr, $singular:expr $(,)?) => {{
+        let n = $n;
+        if n == 1 {
+            $crate::println!("{} {}", n, $singular);
+        } else {
+            $crate::println!("{} {}s", n, $singular);
+        }
+    }};
+    ($n:expr, $singular:expr, $plural:expr $(,)?) => {{
+        let n = $n;
+        if n == 1 {
+            $crate::println!("{} {}", n, $singular);
+        } else {
+            $crate::println!("{} {}", n, $plural);
+        }
+    }};
+}
+
+/// Prints the next spinner frame over the current line and advances the
+/// `usize` counter `state`.
+///
+/// Frames cycle through `|/-\` based on the counter value, see
+/// [`spinner_frame`](crate::io::spinner_frame). Use [`spinner_done!`] to
+/// clear the spinner afterwards.
+#[macro_export]
+macro_rules! spinner {
+    ($state:expr) => {{
+        let state: &mut usize = &mut $state;
+        $crate::print!("\r{}", $crate::io::spinner_frame(*state));
+        *state = state.wrapping_add(1);
+    }};
+}
+
+/// Clears the spinner printed by [`spinner!`].
+#[macro_export]
+macro_rules! spinner_done {
+    () => {
+        $crate::print!("\r \r")
+    };
+}
+
+/// Prints the lines captured in the [`LogRing`](crate::io::LogRing), oldest
+/// first, with a newline after each.
+///
+/// Capture must have been enabled with
+/// [`set_log_capture`](crate::io::set_log_capture). The dump itself is not
+/// captured and ignores the print budget.
+#[macro_export]
+macro_rules! dump_log_ring {
+    () => {
+        $crate::io::__dump_log_ring()
+    };
+}
+
+/// Prints to the standard output, with a newline, only on the bootstrap
+/// processor.
+///
+/// Lets code that runs on every core report once. The running core is
+/// configured with [`set_is_bsp`](crate::io::set_is_bsp) or
+/// [`set_current_cpu`](crate::io::set_current_cpu); arguments are not
+/// evaluated on other cores.
+#[macro_export]
+macro_rules! bsp_println {
+    ($($arg:tt)*) => {{
+        if $crate::io::is_bsp() {
+            $crate::println!($($arg)*);
+        }
+    }};
+}
+
+/// Renders a boot countdown, `Booting in N...`, overwriting the current line.
+///
+/// The caller drives the tick and passes the remaining count each time. A
+/// trailing space covers the last character when the count loses a digit,
+/// and a newline is printed once the count reaches zero.
+#[macro_export]
+macro_rules! countdown_println {
+    ($remaining:expr) => {{
+        let remaining = $remaining;
+        $crate::print!(
+            "\rBooting in {}... {}",
+            remaining,
+            if remaining == 0 { "\n" } else { "" }
+        );
+    }};
+}
+
+/// Prints `label: value` pairs laid out in `$cols` columns, with a newline
+/// after each row.
+///
+/// Every cell is padded to the width of the widest one, so the columns line
+/// up; the last row may be shorter. Labels are cyan and values yellow:
+///
+/// ```ignore
+/// columns_println!(3, ["fpu" => true, "sse" => true, "avx" => false, "cores" => 4]);
+/// ```
+///
+/// The colors are omitted if disabled by [`set_color_enabled`].
+///
+/// [`set_color_enabled`]: crate::io::set_color_enabled
+#[macro_export]
+macro_rules! columns_println {
+    ($cols:expr, [$($label:expr => $value:expr),* $(,)?]) => {
+        $crate::io::__columns_println(
+            $cols,
+            &[$((
+                &$label as &dyn ::core::fmt::Display,
+                &$value as &dyn ::core::fmt::Display,
+            )),*],
+        )
+    };
+}
+
+/// Prints a register dump, one register per line, with each value in both
+/// zero-padded hex and decimal, aligned in columns:
+///
+/// ```ignore
+/// regs_println!("sp" => sp, "pc" => pc, "status" => sstatus);
+/// ```
+///
+/// Values are integers and are shown as 64-bit unsigned.
+#[macro_export]
+macro_rules! regs_println {
+    ($($name:expr => $value:expr),* $(,)?) => {
+        $crate::io::__regs_println(&[$(($name, $value as u64)),*])
+    };
+}
+
+/// Prints a horizontal rule, with a newline, to separate sections of output.
+///
+/// `rule_println!()` prints 80 `-`; `rule_println!(ch, width)` repeats the
+/// character `ch` to fill `width` columns, counting wide characters as two.
+/// An optional third argument colors the rule, unless colors are disabled by
+/// [`set_color_enabled`](crate::io::set_color_enabled):
+///
+/// ```ignore
+/// rule_println!('=', 40, ColorCode::BrightBlack);
+/// ```
+#[macro_export]
+macro_rules! rule_println {
+    () => {
+        $crate::io::__rule_println('-', 80, None)
+    };
+    ($ch:expr, $width:expr) => {
+        $crate::io::__rule_println($ch, $width, None)
+    };
+    ($ch:expr, $width:expr, $color:expr) => {
+        $crate::io::__rule_println($ch, $width, Some($color))
+    };
+}
+
+/// Prints a matrix as a grid of right-aligned cells, with a header of column
+/// indices and a row index in front of each row, all followed by newlines.
+///
+/// `$rows` is a slice, array or `Vec` of rows, each a slice, array or `Vec`
+/// of `Display` values. Every cell is padded to the widest value, and rows
+/// shorter than the longest one are padded with blanks. The indices are
+/// dimmed; given a color, cells that differ from their type's default (zero
+/// for numbers) are highlighted in it:
+///
+/// ```ignore
+/// matrix_println!(pte_flags, ColorCode::Yellow);
+/// ```
+///
+/// The colors are omitted if disabled by
+/// [`set_color_enabled`](crate::io::set_color_enabled).
+#[macro_export]
+macro_rules! matrix_println {
+    ($rows:expr) => {
+        $crate::io::__matrix_println(&$rows[..], None)
+    };
+    ($rows:expr, $highlight:expr) => {
+        $crate::io::__matrix_println(&$rows[..], Some($highlight))
+    };
+}
+
+/// Prints a value followed by its address, `<value> @ 0x<address>`, with a
+/// newline.
+///
+/// `$place` is a place expression such as a variable or a field; it is
+/// borrowed, not moved, and evaluated once.
+#[macro_export]
+macro_rules! addr_println {
+    ($place:expr) => {{
+        let place = &$place;
+        $crate::println!("{} @ {:p}", place, place as *const _);
+    }};
+}
+
+/// Prints to the standard output, with a newline, indented by the current
+/// level set with [`indent`] and [`dedent`].
+///
+/// Each level prepends one unit, two spaces unless changed with
+/// [`set_indent_unit`]. Only the first line of a multi-line message is
+/// indented.
+///
+/// ```ignore
+/// iprintln!("mounting filesystems");
+/// indent();
+/// iprintln!("/ on ramfs");
+/// dedent();
+/// ```
+///
+/// [`indent`]: crate::io::indent
+/// [`dedent`]: crate::io::dedent
+/// [`set_indent_unit`]: crate::io::set_indent_unit
+#[macro_export]
+macro_rules! iprintln {
+    () => {
+        $crate::io::__iprintln(format_args!(""))
+    };
+    ($($arg:tt)*) => {
+        $crate::io::__iprintln(format_args!($($arg)*))
+    };
+}
+
+/// Prints a minimal one-line JSON object, with a newline, e.g.
+/// `{"cpus":4,"model":"riscv64"}`.
+///
+/// Keys can be any `Display` and are always quoted. Values implement
+/// [`JsonValue`](crate::io::JsonValue): numbers and booleans are printed
+/// bare, strings and characters quoted with `"`, `\` and control characters
+/// escaped.
+///
+/// ```ignore
+/// json_println!("cpus" => 4, "model" => "riscv64");
+/// ```
+#[macro_export]
+macro_rules! json_println {
+    ($($key:expr => $value:expr),* $(,)?) => {
+        $crate::io::__json_println(&[$((
+            &$key as &dyn ::core::fmt::Display,
+            &$value as &dyn $crate::io::JsonValue,
+        )),*])
+    };
+}
+
+/// Prints a message hard-wrapped at the width set with
+/// [`set_terminal_width`](crate::io::set_terminal_width), 80 columns by
+/// default.
+///
+/// Lines break at spaces where possible; a word wider than a line is split.
+/// Escape sequences take no columns and wide characters two, see
+/// [`display_width`](crate::io::display_width). Newlines in the message are
+/// kept. With `indent: n`, continuation lines are indented by `n` spaces.
+/// Messages longer than 1024 bytes are printed unwrapped.
+///
+/// ```ignore
+/// wrap_println!("{} devices probed: {}", n, names);
+/// wrap_println!(indent: 4, "cmdline: {}", cmdline);
+/// ```
+#[macro_export]
+macro_rules! wrap_println {
+    (indent: $indent:expr, $($arg:tt)+) => {
+        $crate::io::__wrap_println($indent, format_args!($($arg)+))
+    };
+    ($($arg:tt)+) => {
+        $crate::io::__wrap_println(0, format_args!($($arg)+))
+    };
+}